@@ -1,51 +1,58 @@
-use std::sync::atomic::AtomicU8;
-
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use game_of_life::gameoflife::*;
-use ndarray::{self, Array1};
-use rand::{self, Rng};
-
-fn criterion_benchmark(c: &mut Criterion) {
-    let mut rng = rand::thread_rng();
-
-    let numx: usize = 100;
-    let numy: usize = 100;
-    let field_vec_std: Vec<u8> = (0..numx * numy).map(|_| rng.gen_bool(0.3) as u8).collect();
-    let field_vec_conv = field_vec_std.clone();
-
-    let field_std = Array1::<u8>::from_vec(field_vec_std)
-        .map(|elem| AtomicU8::new(*elem))
-        .into_shape((numx, numy))
-        .unwrap();
-    let field_conv = Array1::<u8>::from_vec(field_vec_conv)
-        .into_shape((numx, numy))
-        .unwrap();
-
-    let rules_std = Rule::new(
-        LifeRule::Raw([false, false, true, true, false, false, false, false, false]),
-        LifeRule::Raw([false, false, false, true, false, false, false, false, false]),
-        1,
-        NeighborRule::Moore,
-    );
-    let rules_conv = rules_std.clone();
+use rand::{rngs::StdRng, SeedableRng};
+
+fn neighbor_benchmark(c: &mut Criterion, neighbor: NeighborRule) {
+    let mut group = c.benchmark_group(neighbor.to_string());
+    group.sample_size(10);
+
+    for size in [100, 500] {
+        for state in [1, 16] {
+            // A square field where 30% of the cells are alive with value `state`.
+            let rules = Rule::new(
+                LifeRule::Raw([false, false, true, true, false, false, false, false, false]),
+                LifeRule::Raw([false, false, false, true, false, false, false, false, false]),
+                state,
+                neighbor.clone(),
+            )
+            .unwrap();
+
+            // The multi-state arithmetic only costs extra in the convolution.
+            if state == 1 {
+                let mut rng = StdRng::seed_from_u64(42);
+                let mut gol_std =
+                    GameOfLifeStd::<u8>::random(size, size, rules.clone(), 0.3, &mut rng);
+                group.bench_function(BenchmarkId::new("GOL Std", size), |b| {
+                    b.iter(|| {
+                        for _ in 0..20 {
+                            gol_std.compute_next_generation();
+                        }
+                    })
+                });
+            }
 
-    let mut gol_std = GameOfLifeStd::new(field_std, rules_std);
-    let mut gol_conv = GameOfLifeConvolution::new(field_conv, rules_conv);
+            let mut rng = StdRng::seed_from_u64(42);
+            let mut gol_conv =
+                GameOfLifeConvolution::<u8>::random(size, size, rules, 0.3, &mut rng);
+            group.bench_function(
+                BenchmarkId::new(format!("GOL Conv state {state}"), size),
+                |b| {
+                    b.iter(|| {
+                        for _ in 0..20 {
+                            gol_conv.compute_next_generation();
+                        }
+                    })
+                },
+            );
+        }
+    }
+
+    group.finish();
+}
 
-    c.bench_function("GOL Std", |b| {
-        b.iter(|| {
-            for _ in 0..20 {
-                gol_std.compute_next_generation()
-            }
-        })
-    });
-    c.bench_function("GOL Conv", |b| {
-        b.iter(|| {
-            for _ in 0..20 {
-                gol_conv.compute_next_generation()
-            }
-        })
-    });
+fn criterion_benchmark(c: &mut Criterion) {
+    neighbor_benchmark(c, NeighborRule::Moore);
+    neighbor_benchmark(c, NeighborRule::VonNeumann);
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -0,0 +1,112 @@
+//! # Activity
+//! Contains the heat map of living cells saved with `--activity` at the end of a run.
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::rc::Rc;
+
+use game_of_life::gameoflife::*;
+use game_of_life::presentation::OnGeneration;
+use ndarray::Array2;
+
+/// Number of generations in which every cell was alive, indexed by `[x, y]` like the field
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Activity {
+    pub alive: Array2<u32>,
+    /// Number of recorded generations, which includes the initial field
+    pub generations: u32,
+}
+
+impl Activity {
+    /// Counts the living cells of `gameoflife` and returns a hook for the presentations that counts those of every generation computed after it.
+    pub fn hook<G: GameOfLife>(activity: &Rc<RefCell<Self>>, gameoflife: &G) -> OnGeneration<G> {
+        activity
+            .borrow_mut()
+            .record(&gameoflife.snapshot(), gameoflife.state());
+        let activity = Rc::clone(activity);
+        Box::new(move |_, gameoflife| {
+            activity
+                .borrow_mut()
+                .record(&gameoflife.snapshot(), gameoflife.state())
+        })
+    }
+
+    /// Counts the living cells of `field`.
+    fn record<T: Cell>(&mut self, field: &Array2<T>, state: T) {
+        if self.alive.dim() != field.dim() {
+            self.alive = Array2::zeros(field.dim());
+        }
+        self.alive
+            .zip_mut_with(field, |alive, &cell| *alive += (cell == state) as u32);
+        self.generations += 1;
+    }
+
+    /// Returns the grayscale pixels row by row, where the most active cell is white and cells that were never alive are black.
+    fn pixels(&self, scale: usize) -> Vec<u8> {
+        let (numx, numy) = self.alive.dim();
+        let max = self.alive.iter().copied().max().unwrap_or(0).max(1);
+        let mut pixels = Vec::with_capacity(numx * numy * scale * scale);
+        for y in 0..numy * scale {
+            pixels.extend(
+                (0..numx * scale)
+                    .map(|x| (255 * self.alive[[x / scale, y / scale]] as u64 / max as u64) as u8),
+            );
+        }
+        pixels
+    }
+
+    /// Saves the heat map as a grayscale PNG with every cell as a block of `scale` x `scale` pixels.
+    pub fn write_png(&self, path: &Path, scale: usize) -> Result<(), png::EncodingError> {
+        let (numx, numy) = self.alive.dim();
+        let mut encoder = png::Encoder::new(
+            BufWriter::new(File::create(path)?),
+            (numx * scale) as u32,
+            (numy * scale) as u32,
+        );
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()?
+            .write_image_data(&self.pixels(scale))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn blinker() {
+        let field = Array2::from_shape_fn((5, 5), |(x, y)| (y == 2 && (1..4).contains(&x)) as u8);
+        let activity = Rc::default();
+        let mut gol = GameOfLifeStd::new(field.clone(), Rule::default());
+        let mut hook = Activity::hook(&activity, &gol);
+        for generation in 1..=4 {
+            gol.compute_next_generation();
+            hook(generation, &gol);
+        }
+        let activity = activity.borrow().clone();
+        assert_eq!(activity.generations, 5);
+
+        // The center is always alive, the horizontal arms in 3 and the vertical arms in 2 of the 5 generations.
+        for ((x, y), &alive) in activity.alive.indexed_iter() {
+            let expected = match (x, y) {
+                (2, 2) => 5,
+                (1 | 3, 2) => 3,
+                (2, 1 | 3) => 2,
+                _ => 0,
+            };
+            assert_eq!(alive, expected, "({x}, {y})");
+        }
+
+        let pixels = activity.pixels(2);
+        assert_eq!(pixels.len(), 10 * 10);
+        let pixel = |x: usize, y: usize| pixels[y * 10 + x];
+        assert_eq!((pixel(4, 4), pixel(5, 5)), (255, 255));
+        assert_eq!(pixel(2, 4) as u32, 255 * 3 / 5);
+        assert_eq!(pixel(4, 2) as u32, 255 * 2 / 5);
+        assert_eq!(pixel(0, 0), 0);
+    }
+}
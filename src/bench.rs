@@ -0,0 +1,144 @@
+//! # Benchmark
+//! Contains the throughput measured with the `bench` subcommand.
+
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+use game_of_life::builder::Algorithm;
+use game_of_life::gameoflife::*;
+use game_of_life::presentation::OnGeneration;
+use indicatif::DecimalBytes;
+
+/// Timing of a run without any presentation
+#[derive(Clone, Debug, PartialEq)]
+pub struct Benchmark {
+    pub algorithm: Algorithm,
+    pub numx: usize,
+    pub numy: usize,
+    /// Number of computed generations
+    pub generations: usize,
+    /// Wall-clock time of all generations
+    pub elapsed: Duration,
+    /// Time of the fastest and the slowest generation
+    pub fastest: Duration,
+    pub slowest: Duration,
+    /// Peak resident memory of the process in bytes, only known on Linux
+    pub peak_memory: Option<u64>,
+}
+
+impl Benchmark {
+    /// Computes `generations` generations as fast as possible and times them.
+    /// `on_generation` is called after every generation like in the presentations, but is not part of its time.
+    pub fn run<G: GameOfLife>(
+        mut gameoflife: G,
+        generations: usize,
+        algorithm: Algorithm,
+        mut on_generation: Option<OnGeneration<G>>,
+    ) -> Self {
+        let mut fastest = Duration::MAX;
+        let mut slowest = Duration::ZERO;
+        let begin = Instant::now();
+        for generation in 1..=generations {
+            let start = Instant::now();
+            gameoflife.compute_next_generation();
+            let elapsed = start.elapsed();
+            fastest = fastest.min(elapsed);
+            slowest = slowest.max(elapsed);
+            if let Some(ref mut on_generation) = on_generation {
+                on_generation(generation, &gameoflife);
+            }
+        }
+        let elapsed = begin.elapsed();
+
+        Self {
+            algorithm,
+            numx: gameoflife.numx(),
+            numy: gameoflife.numy(),
+            generations,
+            elapsed,
+            fastest: fastest.min(slowest),
+            slowest,
+            peak_memory: peak_memory(),
+        }
+    }
+
+    pub fn generations_per_second(&self) -> f64 {
+        self.generations as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+impl Display for Benchmark {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cells = self.numx * self.numy;
+        writeln!(f, "Algorithm: {}", self.algorithm)?;
+        writeln!(f, "Field: {}x{} cells", self.numx, self.numy)?;
+        writeln!(f, "Generations: {}", self.generations)?;
+        writeln!(f, "Time: {:.2?}", self.elapsed)?;
+        writeln!(
+            f,
+            "Generations per second: {:.1}",
+            self.generations_per_second()
+        )?;
+        writeln!(
+            f,
+            "Cells per second: {:.3e}",
+            self.generations_per_second() * cells as f64
+        )?;
+        write!(
+            f,
+            "Fastest/slowest generation: {:.2?}/{:.2?}",
+            self.fastest, self.slowest
+        )?;
+        if let Some(peak_memory) = self.peak_memory {
+            write!(f, "\nPeak memory: {}", DecimalBytes(peak_memory))?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the peak resident memory of the process from `/proc`, which only exists on Linux.
+fn peak_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kilobytes: u64 = line
+        .trim_start_matches("VmHWM:")
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kilobytes * 1024)
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use game_of_life::patterns::Pattern;
+
+    use super::*;
+
+    #[test]
+    fn throughput() {
+        let field = Pattern::Blinker.place(20, 10, 1).unwrap();
+        let gol = GameOfLifeStd::new(field, Rule::default());
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let on_generation: OnGeneration<_> = Box::new({
+            let calls = Rc::clone(&calls);
+            move |generation, _: &GameOfLifeStd| calls.borrow_mut().push(generation)
+        });
+        let benchmark = Benchmark::run(gol, 50, Algorithm::Std, Some(on_generation));
+        assert_eq!(*calls.borrow(), (1..=50).collect::<Vec<_>>());
+
+        assert_eq!((benchmark.numx, benchmark.numy), (20, 10));
+        assert_eq!(benchmark.generations, 50);
+        assert!(benchmark.fastest <= benchmark.slowest);
+        assert!(benchmark.slowest <= benchmark.elapsed);
+        assert!(benchmark.generations_per_second() > 0.);
+
+        let text = benchmark.to_string();
+        assert!(text.contains("Field: 20x10 cells"));
+        assert!(text.contains("Generations per second: "));
+    }
+}
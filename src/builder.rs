@@ -0,0 +1,747 @@
+//! # Builder
+//! Contains a builder to configure and construct a Game of Life with any of the algorithms.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use ndarray::Array2;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::gameoflife::*;
+#[cfg(feature = "gpu")]
+use crate::gpu::GameOfLifeGpu;
+use crate::patterns::pad;
+
+/// Available algorithms to calculate the time steps.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Algorithm {
+    Std,
+    Conv,
+    #[cfg(feature = "gpu")]
+    Gpu,
+}
+
+impl Algorithm {
+    /// Returns all algorithms available in this build.
+    pub fn all() -> Vec<Algorithm> {
+        vec![
+            Algorithm::Std,
+            Algorithm::Conv,
+            #[cfg(feature = "gpu")]
+            Algorithm::Gpu,
+        ]
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Algorithm, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "std" => Ok(Algorithm::Std),
+            "standard" => Ok(Algorithm::Std),
+            "conv" => Ok(Algorithm::Conv),
+            "convolution" => Ok(Algorithm::Conv),
+            #[cfg(feature = "gpu")]
+            "gpu" => Ok(Algorithm::Gpu),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for Algorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Algorithm::Std => write!(f, "standard"),
+            Algorithm::Conv => write!(f, "convolution"),
+            #[cfg(feature = "gpu")]
+            Algorithm::Gpu => write!(f, "GPU"),
+        }
+    }
+}
+
+/// Symmetries of a random initial field.
+///
+/// For odd dimensions, the center row or column is shared by both halves.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Symmetry {
+    #[default]
+    None,
+    /// The right half mirrors the left half.
+    Horizontal,
+    /// The bottom half mirrors the top half.
+    Vertical,
+    /// All quadrants mirror the top-left one.
+    Both,
+    /// The field is unchanged by a rotation by 180°.
+    Rotational,
+}
+
+impl Symmetry {
+    /// Returns the cell whose value the cell at `(x, y)` copies in a field with the given dimensions.
+    fn source(&self, x: usize, y: usize, numx: usize, numy: usize) -> (usize, usize) {
+        let (mirror_x, mirror_y) = (numx - 1 - x, numy - 1 - y);
+        match *self {
+            Symmetry::None => (x, y),
+            Symmetry::Horizontal => (x.min(mirror_x), y),
+            Symmetry::Vertical => (x, y.min(mirror_y)),
+            Symmetry::Both => (x.min(mirror_x), y.min(mirror_y)),
+            Symmetry::Rotational => (x, y).min((mirror_x, mirror_y)),
+        }
+    }
+}
+
+impl FromStr for Symmetry {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Symmetry, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "none" => Ok(Symmetry::None),
+            "horizontal" => Ok(Symmetry::Horizontal),
+            "vertical" => Ok(Symmetry::Vertical),
+            "both" => Ok(Symmetry::Both),
+            "rotational" => Ok(Symmetry::Rotational),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for Symmetry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Symmetry::None => write!(f, "none"),
+            Symmetry::Horizontal => write!(f, "horizontal"),
+            Symmetry::Vertical => write!(f, "vertical"),
+            Symmetry::Both => write!(f, "both"),
+            Symmetry::Rotational => write!(f, "rotational"),
+        }
+    }
+}
+
+/// Values of the living cells of a random initial field.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum InitStates {
+    /// All living cells have the state of the rule, i.e. are fully alive.
+    #[default]
+    Max,
+    /// Every living cell has a random value between 1 and the state, so that rules with more states start with decaying cells.
+    Random,
+}
+
+impl FromStr for InitStates {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<InitStates, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "max" => Ok(InitStates::Max),
+            "uniform" => Ok(InitStates::Max),
+            "random" => Ok(InitStates::Random),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for InitStates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            InitStates::Max => write!(f, "max"),
+            InitStates::Random => write!(f, "random"),
+        }
+    }
+}
+
+/// Reasons why a [`GameBuilder`] cannot build a Game of Life.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BuildError {
+    /// The algorithm only supports `BorderRule::Constant`.
+    UnsupportedBorder(Algorithm, BorderRule),
+    /// The algorithm does not support `NeighborRule::Custom` or `NeighborRule::VonNeumannR`.
+    UnsupportedKernel(Algorithm),
+    /// The field has no cells or too many, see [`GameBuilder::max_cells`].
+    InvalidDimensions(GolError),
+    /// The rule has more states than the `u8` cells of the games have room for.
+    InvalidState(GolError),
+    /// The von Neumann radius of the rule is too large, see [`check_neighbor`].
+    InvalidNeighbor(GolError),
+    /// The probability of [`GameBuilder::random`] is not between 0 and 1.
+    InvalidProbability(f64),
+    /// The map of [`GameBuilder::density_map`] has no values or a NaN.
+    InvalidDensityMap,
+    /// No GPU is available for `Algorithm::Gpu`.
+    #[cfg(feature = "gpu")]
+    NoGpu,
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildError::UnsupportedBorder(algorithm, border) => write!(
+                f,
+                "The {algorithm} algorithm does not support the {border} border rule"
+            ),
+            BuildError::InvalidDimensions(e)
+            | BuildError::InvalidState(e)
+            | BuildError::InvalidNeighbor(e) => write!(f, "{e}"),
+            BuildError::InvalidProbability(probability) => write!(
+                f,
+                "The probability has to be between 0 and 1, but is {probability}"
+            ),
+            BuildError::InvalidDensityMap => {
+                write!(f, "The density map needs at least one value and no NaN")
+            }
+            BuildError::UnsupportedKernel(algorithm) => {
+                write!(
+                    f,
+                    "The {algorithm} algorithm does not support custom kernels or larger radii"
+                )
+            }
+            #[cfg(feature = "gpu")]
+            BuildError::NoGpu => write!(f, "No GPU available"),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// A Game of Life using one of the algorithms, as built by [`GameBuilder`].
+#[derive(Clone)]
+pub enum Game {
+    Std(GameOfLifeStd),
+    Conv(GameOfLifeConvolution),
+    #[cfg(feature = "gpu")]
+    Gpu(GameOfLifeGpu),
+}
+
+/// Calls the same expression on whichever algorithm the game uses.
+macro_rules! dispatch {
+    ($game:expr, $gol:ident => $call:expr) => {
+        match $game {
+            Game::Std($gol) => $call,
+            Game::Conv($gol) => $call,
+            #[cfg(feature = "gpu")]
+            Game::Gpu($gol) => $call,
+        }
+    };
+}
+
+impl GameOfLife for Game {
+    type Data = u8;
+
+    /// Generate a new Game of Life using the convolution.
+    fn new(field: Array2<u8>, rules: Rule) -> Self {
+        Game::Conv(GameOfLifeConvolution::new(field, rules))
+    }
+
+    fn to_board(&self) -> Board {
+        dispatch!(self, gol => gol.to_board())
+    }
+
+    fn compute_next_generation(&mut self) -> bool {
+        dispatch!(self, gol => gol.compute_next_generation())
+    }
+
+    fn cell(&self, x: usize, y: usize) -> Option<u8> {
+        dispatch!(self, gol => gol.cell(x, y))
+    }
+
+    fn set_cell(&mut self, x: usize, y: usize, value: u8) -> bool {
+        dispatch!(self, gol => gol.set_cell(x, y, value))
+    }
+
+    fn numx(&self) -> usize {
+        dispatch!(self, gol => gol.numx())
+    }
+
+    fn numy(&self) -> usize {
+        dispatch!(self, gol => gol.numy())
+    }
+
+    fn state(&self) -> u8 {
+        dispatch!(self, gol => gol.state())
+    }
+
+    fn snapshot(&self) -> Array2<u8> {
+        dispatch!(self, gol => gol.snapshot())
+    }
+
+    fn neighbor_counts(&self) -> Array2<u8> {
+        dispatch!(self, gol => gol.neighbor_counts())
+    }
+}
+
+/// How the initial field is generated.
+#[derive(Clone, Debug)]
+enum Initialization {
+    Empty,
+    Random { probability: f64, seed: Option<u64> },
+    Field(Array2<u8>),
+}
+
+/// Builder for a [`Game`].
+///
+/// By default, the field is empty with 10x10 cells, uses [`Rule::default`] and the convolution algorithm.
+///
+/// ```
+/// use game_of_life::builder::*;
+/// use game_of_life::gameoflife::*;
+///
+/// let mut game = GameBuilder::new()
+///     .dimensions(20, 10)
+///     .rule("B36/S23".parse().unwrap())
+///     .random(0.3, Some(42))
+///     .algorithm(Algorithm::Std)
+///     .build()
+///     .unwrap();
+/// game.compute_next_generation();
+/// assert_eq!((game.numx(), game.numy()), (20, 10));
+/// ```
+#[derive(Clone, Debug)]
+pub struct GameBuilder {
+    numx: usize,
+    numy: usize,
+    rule: Rule,
+    initialization: Initialization,
+    density: Option<Array2<f64>>,
+    symmetry: Symmetry,
+    init_states: InitStates,
+    algorithm: Algorithm,
+    border: BorderRule,
+    margin: usize,
+    max_cells: usize,
+}
+
+impl Default for GameBuilder {
+    fn default() -> Self {
+        Self {
+            numx: 10,
+            numy: 10,
+            rule: Rule::default(),
+            initialization: Initialization::Empty,
+            density: None,
+            symmetry: Symmetry::None,
+            init_states: InitStates::Max,
+            algorithm: Algorithm::Conv,
+            border: BorderRule::default(),
+            margin: 0,
+            max_cells: MAX_CELLS,
+        }
+    }
+}
+
+impl GameBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of columns and rows, which is ignored for fields set with [`GameBuilder::pattern`].
+    pub fn dimensions(mut self, numx: usize, numy: usize) -> Self {
+        self.numx = numx;
+        self.numy = numy;
+        self
+    }
+
+    pub fn rule(mut self, rule: Rule) -> Self {
+        self.rule = rule;
+        self
+    }
+
+    /// Fills the field randomly, with each cell being alive with `probability`.
+    /// The same seed always produces the same field.
+    pub fn random(mut self, probability: f64, seed: Option<u64>) -> Self {
+        self.initialization = Initialization::Random { probability, seed };
+        self
+    }
+
+    /// Scales the probability of [`GameBuilder::random`] for every cell by the value of the map between 0 and 1,
+    /// which is stretched to the dimensions of the field. Patterns are left as they are.
+    pub fn density_map(mut self, density: Array2<f64>) -> Self {
+        self.density = Some(density);
+        self
+    }
+
+    /// Mirrors the random field of [`GameBuilder::random`], patterns are left as they are.
+    pub fn symmetry(mut self, symmetry: Symmetry) -> Self {
+        self.symmetry = symmetry;
+        self
+    }
+
+    /// Sets the values of the living cells of the random field of [`GameBuilder::random`], patterns are left as they are.
+    pub fn init_states(mut self, init_states: InitStates) -> Self {
+        self.init_states = init_states;
+        self
+    }
+
+    /// Starts from the given field, whose shape also sets the dimensions.
+    pub fn pattern(mut self, field: Array2<u8>) -> Self {
+        self.initialization = Initialization::Field(field);
+        self
+    }
+
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Sets how the field is extended beyond its borders, which only the convolution supports besides `BorderRule::Constant`.
+    pub fn border(mut self, border: BorderRule) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Surrounds the field with `margin` dead cells on every side, which adds to its dimensions.
+    /// The random field of [`GameBuilder::random`] keeps its dimensions inside the margin, and patterns end up centered.
+    pub fn margin(mut self, margin: usize) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Sets the maximum number of cells of the field, which is [`MAX_CELLS`] by default.
+    pub fn max_cells(mut self, max_cells: usize) -> Self {
+        self.max_cells = max_cells;
+        self
+    }
+
+    /// Generates the initial field and constructs the Game of Life.
+    pub fn build(self) -> Result<Game, BuildError> {
+        if self.algorithm != Algorithm::Conv && self.border != BorderRule::Constant {
+            return Err(BuildError::UnsupportedBorder(self.algorithm, self.border));
+        }
+        // Checked before generating the field, so that huge fields are never allocated.
+        let (numx, numy) = match self.initialization {
+            Initialization::Field(ref field) => field.dim(),
+            _ => (self.numx, self.numy),
+        };
+        let margin = self.margin.saturating_mul(2);
+        check_dimensions(
+            numx.saturating_add(margin),
+            numy.saturating_add(margin),
+            self.max_cells,
+        )
+        .map_err(BuildError::InvalidDimensions)?;
+        let state = check_state::<u8>(self.rule.state).map_err(BuildError::InvalidState)?;
+        check_neighbor(&self.rule.neighbor).map_err(BuildError::InvalidNeighbor)?;
+
+        let field =
+            match self.initialization {
+                Initialization::Empty => Array2::zeros((self.numx, self.numy)),
+                Initialization::Random { probability, seed } => {
+                    if !(0.0..=1.0).contains(&probability) {
+                        return Err(BuildError::InvalidProbability(probability));
+                    }
+                    if self.density.as_ref().is_some_and(|density| {
+                        density.is_empty() || density.iter().any(|d| d.is_nan())
+                    }) {
+                        return Err(BuildError::InvalidDensityMap);
+                    }
+                    let mut rng = match seed {
+                        Some(seed) => StdRng::seed_from_u64(seed),
+                        None => StdRng::from_entropy(),
+                    };
+                    let probability_at = |x: usize, y: usize| match self.density {
+                        Some(ref density) => {
+                            let (width, height) = density.dim();
+                            let density = density[[x * width / self.numx, y * height / self.numy]];
+                            probability * density.clamp(0., 1.)
+                        }
+                        None => probability,
+                    };
+                    // The values are drawn from a generator split off first, so that the same seed keeps the same living cells for all `InitStates`.
+                    let mut value_rng =
+                        StdRng::from_rng(&mut rng).expect("seeding from a StdRng cannot fail");
+                    let field = Array2::from_shape_fn((self.numx, self.numy), |(x, y)| {
+                        if !rng.gen_bool(probability_at(x, y)) {
+                            return 0;
+                        }
+                        match self.init_states {
+                            InitStates::Random if state > 0 => value_rng.gen_range(1..=state),
+                            _ => state,
+                        }
+                    });
+                    Array2::from_shape_fn(field.dim(), |(x, y)| {
+                        field[self.symmetry.source(x, y, self.numx, self.numy)]
+                    })
+                }
+                Initialization::Field(field) => field,
+            };
+        let field = match self.margin {
+            0 => field,
+            margin => pad(&field, margin),
+        };
+
+        Ok(match self.algorithm {
+            Algorithm::Std => Game::Std(GameOfLifeStd::new(field, self.rule)),
+            Algorithm::Conv => {
+                Game::Conv(GameOfLifeConvolution::new(field, self.rule).with_border(self.border))
+            }
+            #[cfg(feature = "gpu")]
+            Algorithm::Gpu => {
+                if matches!(
+                    self.rule.neighbor,
+                    NeighborRule::Custom(_) | NeighborRule::VonNeumannR(_)
+                ) {
+                    return Err(BuildError::UnsupportedKernel(self.algorithm));
+                }
+                Game::Gpu(GameOfLifeGpu::try_new(field, self.rule).ok_or(BuildError::NoGpu)?)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn seeded_random() {
+        let builder = GameBuilder::new()
+            .dimensions(8, 5)
+            .rule(Rule::from_str("23/3/4").unwrap())
+            .random(0.5, Some(7));
+        let std = builder.clone().algorithm(Algorithm::Std).build().unwrap();
+        let conv = builder.build().unwrap();
+
+        assert!(matches!(std, Game::Std(_)));
+        assert!(matches!(conv, Game::Conv(_)));
+        assert_eq!(std.snapshot(), conv.snapshot());
+        assert_eq!(std.snapshot().dim(), (8, 5));
+        assert!(std.snapshot().iter().all(|&cell| cell == 0 || cell == 3));
+    }
+
+    #[test]
+    fn margin() {
+        let builder = GameBuilder::new()
+            .dimensions(6, 4)
+            .random(1., Some(3))
+            .margin(2);
+        let field = builder.clone().build().unwrap().snapshot();
+        assert_eq!(field.dim(), (10, 8));
+        // Only the interior is filled.
+        assert_eq!(field.sum(), 6 * 4);
+        assert!(field
+            .slice(ndarray::s![2..8, 2..6])
+            .iter()
+            .all(|&cell| cell == 1));
+
+        let blinker = Array2::ones((3, 1));
+        let field = GameBuilder::new()
+            .pattern(blinker.clone())
+            .margin(1)
+            .build()
+            .unwrap()
+            .snapshot();
+        assert_eq!(field, pad(&blinker, 1));
+
+        assert_eq!(
+            builder.max_cells(79).build().err(),
+            Some(BuildError::InvalidDimensions(GolError::InvalidDimensions {
+                numx: 10,
+                numy: 8,
+                max_cells: 79
+            }))
+        );
+        assert!(GameBuilder::new().margin(usize::MAX).build().is_err());
+    }
+
+    #[test]
+    fn random_init_states() {
+        let builder = GameBuilder::new()
+            .dimensions(20, 20)
+            .rule(Rule::from_str("23/3/5").unwrap())
+            .random(0.5, Some(7));
+        let max = builder.clone().build().unwrap().snapshot();
+        let random = builder
+            .clone()
+            .init_states(InitStates::Random)
+            .build()
+            .unwrap()
+            .snapshot();
+
+        // The same cells are alive, but at all values from 1 to the state.
+        for (&max, &random) in max.iter().zip(random.iter()) {
+            assert_eq!(max == 0, random == 0);
+            assert!(random <= 4);
+        }
+        for value in 1..=4 {
+            assert!(random.iter().any(|&cell| cell == value));
+        }
+        assert_eq!(
+            builder
+                .init_states(InitStates::Random)
+                .build()
+                .unwrap()
+                .snapshot(),
+            random
+        );
+        assert_eq!(InitStates::from_str("uniform"), Ok(InitStates::Max));
+    }
+
+    #[test]
+    fn density_map() {
+        // Only the right half of the map and therefore of the field is alive.
+        let density = Array2::from_shape_fn((2, 3), |(x, _)| x as f64);
+        let builder = GameBuilder::new()
+            .dimensions(9, 5)
+            .random(1.0, Some(1))
+            .density_map(density);
+        let field = builder.clone().build().unwrap().snapshot();
+        for ((x, _), &cell) in field.indexed_iter() {
+            assert_eq!(cell, (x >= 5) as u8);
+        }
+
+        assert_eq!(
+            builder.random(0.0, Some(1)).build().unwrap().snapshot(),
+            Array2::<u8>::zeros((9, 5))
+        );
+    }
+
+    #[test]
+    fn symmetric_random() {
+        let builder = GameBuilder::new().dimensions(7, 6).random(0.5, Some(3));
+        let plain = builder.clone().build().unwrap().snapshot();
+        let field = |symmetry| {
+            builder
+                .clone()
+                .symmetry(symmetry)
+                .build()
+                .unwrap()
+                .snapshot()
+        };
+
+        let horizontal = field(Symmetry::Horizontal);
+        let vertical = field(Symmetry::Vertical);
+        let both = field(Symmetry::Both);
+        let rotational = field(Symmetry::Rotational);
+        for x in 0..7 {
+            for y in 0..6 {
+                assert_eq!(horizontal[[x, y]], horizontal[[6 - x, y]]);
+                assert_eq!(vertical[[x, y]], vertical[[x, 5 - y]]);
+                assert_eq!(both[[x, y]], both[[6 - x, 5 - y]]);
+                assert_eq!(both[[x, y]], both[[6 - x, y]]);
+                assert_eq!(rotational[[x, y]], rotational[[6 - x, 5 - y]]);
+            }
+        }
+        // The same seed keeps the top-left quadrant, including the shared center column.
+        for x in 0..4 {
+            for y in 0..3 {
+                assert_eq!(both[[x, y]], plain[[x, y]]);
+            }
+        }
+        assert_eq!(field(Symmetry::None), plain);
+    }
+
+    #[test]
+    fn invalid_dimensions() {
+        let error = |numx, numy, max_cells| {
+            Some(BuildError::InvalidDimensions(GolError::InvalidDimensions {
+                numx,
+                numy,
+                max_cells,
+            }))
+        };
+        assert_eq!(
+            GameBuilder::new().dimensions(0, 5).build().err(),
+            error(0, 5, MAX_CELLS)
+        );
+        assert_eq!(
+            GameBuilder::new()
+                .dimensions(usize::MAX, usize::MAX)
+                .random(0.5, None)
+                .build()
+                .err(),
+            error(usize::MAX, usize::MAX, MAX_CELLS)
+        );
+        assert_eq!(
+            GameBuilder::new()
+                .pattern(Array2::zeros((5, 4)))
+                .max_cells(19)
+                .build()
+                .err(),
+            error(5, 4, 19)
+        );
+        assert!(GameBuilder::new()
+            .dimensions(5, 4)
+            .max_cells(20)
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn unsupported_border() {
+        assert_eq!(
+            GameBuilder::new()
+                .algorithm(Algorithm::Std)
+                .border(BorderRule::Wrap)
+                .build()
+                .err(),
+            Some(BuildError::UnsupportedBorder(
+                Algorithm::Std,
+                BorderRule::Wrap
+            ))
+        );
+    }
+
+    #[test]
+    fn invalid_probability() {
+        for probability in [-0.1, 2.0, f64::INFINITY] {
+            assert_eq!(
+                GameBuilder::new().random(probability, None).build().err(),
+                Some(BuildError::InvalidProbability(probability))
+            );
+        }
+        assert!(matches!(
+            GameBuilder::new().random(f64::NAN, None).build().err(),
+            Some(BuildError::InvalidProbability(probability)) if probability.is_nan()
+        ));
+
+        for density in [Array2::zeros((0, 3)), Array2::from_elem((2, 2), f64::NAN)] {
+            assert_eq!(
+                GameBuilder::new()
+                    .random(0.5, None)
+                    .density_map(density)
+                    .build()
+                    .err(),
+                Some(BuildError::InvalidDensityMap)
+            );
+        }
+        // Patterns ignore the density map.
+        assert!(GameBuilder::new()
+            .pattern(Array2::zeros((3, 3)))
+            .density_map(Array2::zeros((0, 0)))
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn invalid_state() {
+        assert_eq!(
+            GameBuilder::new()
+                .rule(Rule::from_str("23/3/300").unwrap())
+                .build()
+                .err(),
+            Some(BuildError::InvalidState(GolError::InvalidState {
+                state: 299,
+                wall: 255
+            }))
+        );
+    }
+
+    #[test]
+    fn invalid_neighbor() {
+        // Rules that skip `Rule::new`, e.g. deserialized ones, are checked as well.
+        let rule = Rule {
+            neighbor: NeighborRule::VonNeumannR(5000),
+            ..Rule::default()
+        };
+        assert_eq!(
+            GameBuilder::new().rule(rule).build().err(),
+            Some(BuildError::InvalidNeighbor(GolError::InvalidRadius {
+                radius: 5000,
+                max_radius: MAX_RADIUS
+            }))
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! # Checkpoint
+//! Contains the checkpoints saved with `--checkpoint` and continued with `--resume`.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use game_of_life::builder::Algorithm;
+use game_of_life::gameoflife::*;
+use game_of_life::presentation::OnGeneration;
+use ndarray::{Array2, ShapeError};
+use serde::{Deserialize, Serialize};
+
+/// Everything needed to continue a run with the same frames, saved as JSON.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Number of generations computed before the checkpoint
+    pub generation: usize,
+    pub algorithm: Algorithm,
+    pub border: BorderRule,
+    pub board: Board,
+    /// Seed of the run, from which a resumed run continues the noise
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+impl Checkpoint {
+    pub fn read(reader: impl Read) -> io::Result<Self> {
+        Ok(serde_json::from_reader(BufReader::new(reader))?)
+    }
+
+    pub fn write(&self, writer: impl Write) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        serde_json::to_writer(&mut writer, self)?;
+        writer.flush()
+    }
+
+    /// Returns the field of the board.
+    pub fn field(&self) -> Result<Array2<u8>, ShapeError> {
+        Array2::from_shape_vec((self.board.numx, self.board.numy), self.board.cells.clone())
+    }
+}
+
+/// Saves a [`Checkpoint`] every `every` generations from a hook for the presentations.
+#[derive(Clone, Debug)]
+pub struct Checkpointer {
+    path: PathBuf,
+    every: usize,
+    generation: usize,
+    algorithm: Algorithm,
+    border: BorderRule,
+    seed: Option<u64>,
+}
+
+impl Checkpointer {
+    /// `generation` is the number of generations computed before the initial field, which is only not 0 when resuming.
+    pub fn new(
+        path: PathBuf,
+        every: usize,
+        generation: usize,
+        algorithm: Algorithm,
+        border: BorderRule,
+        seed: Option<u64>,
+    ) -> Self {
+        Self {
+            path,
+            every,
+            generation,
+            algorithm,
+            border,
+            seed,
+        }
+    }
+
+    /// Returns a hook for the presentations that saves the checkpoints and only warns if that fails.
+    pub fn hook<G: GameOfLife<Data = u8>>(self) -> OnGeneration<G> {
+        Box::new(move |generation, gameoflife| {
+            let generation = self.generation + generation;
+            if generation.is_multiple_of(self.every) {
+                if let Err(e) = self.save(generation, gameoflife.to_board()) {
+                    log::warn!(
+                        "could not save the checkpoint of generation {generation} to {}: {e}",
+                        self.path.display()
+                    );
+                }
+            }
+        })
+    }
+
+    /// Writes the checkpoint next to the previous one first, so that an interrupted write never leaves a broken checkpoint behind.
+    fn save(&self, generation: usize, board: Board) -> io::Result<()> {
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        let checkpoint = Checkpoint {
+            generation,
+            algorithm: self.algorithm,
+            border: self.border,
+            board,
+            seed: self.seed,
+        };
+        checkpoint.write(File::create(&temporary)?)?;
+        std::fs::rename(temporary, &self.path)
+    }
+}
+
+/// Reads the checkpoint at `path`.
+pub fn read_checkpoint(path: &Path) -> io::Result<Checkpoint> {
+    Checkpoint::read(File::open(path)?)
+}
+
+#[cfg(test)]
+mod test {
+    use game_of_life::builder::*;
+
+    use super::*;
+    use crate::noise::{Noise, Noisy};
+
+    #[test]
+    fn resume() {
+        let builder = GameBuilder::new()
+            .dimensions(30, 20)
+            .rule("B3/S23/C4".parse().unwrap())
+            .border(BorderRule::Wrap)
+            .random(0.3, Some(42));
+        // Injected in generations 3, 6, 9, ..., so before and after the checkpoint.
+        let noise = Noise {
+            probability: 0.05,
+            every: 3,
+        };
+        let mut uninterrupted = Noisy::new(builder.clone().build().unwrap(), noise, 42);
+        let mut interrupted = Noisy::new(builder.build().unwrap(), noise, 42);
+        for _ in 0..10 {
+            uninterrupted.compute_next_generation();
+            interrupted.compute_next_generation();
+        }
+
+        let checkpoint = Checkpoint {
+            generation: 10,
+            algorithm: Algorithm::Conv,
+            border: BorderRule::Wrap,
+            board: interrupted.to_board(),
+            seed: Some(42),
+        };
+        let mut bytes = Vec::new();
+        checkpoint.write(&mut bytes).unwrap();
+        let restored = Checkpoint::read(bytes.as_slice()).unwrap();
+        assert_eq!(restored, checkpoint);
+
+        let resumed = GameBuilder::new()
+            .rule(restored.board.rule.clone())
+            .algorithm(restored.algorithm)
+            .border(restored.border)
+            .pattern(restored.field().unwrap())
+            .build()
+            .unwrap();
+        let mut resumed =
+            Noisy::new(resumed, noise, restored.seed.unwrap()).with_generation(restored.generation);
+        for _ in 0..10 {
+            uninterrupted.compute_next_generation();
+            resumed.compute_next_generation();
+            assert_eq!(resumed.snapshot(), uninterrupted.snapshot());
+        }
+
+        assert!(Checkpoint::read("{}".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn checkpointer() {
+        let path = std::env::temp_dir().join("game-of-life-checkpointer.json");
+        let _ = std::fs::remove_file(&path);
+        let mut gol = GameOfLifeStd::new(
+            game_of_life::patterns::Pattern::Blinker
+                .place(5, 5, 1)
+                .unwrap(),
+            Rule::default(),
+        );
+        // Resumed after generation 7, so generations 1 and 5 of this run are the multiples 8 and 12.
+        let mut hook = Checkpointer::new(
+            path.clone(),
+            4,
+            7,
+            Algorithm::Std,
+            BorderRule::Constant,
+            Some(1),
+        )
+        .hook();
+        let mut saved = Vec::new();
+        for generation in 1..=5 {
+            gol.compute_next_generation();
+            hook(generation, &gol);
+            if let Ok(checkpoint) = read_checkpoint(&path) {
+                std::fs::remove_file(&path).unwrap();
+                assert_eq!(checkpoint.field().unwrap(), gol.snapshot());
+                assert_eq!(checkpoint.seed, Some(1));
+                saved.push(checkpoint.generation);
+            }
+        }
+        assert_eq!(saved, [8, 12]);
+    }
+
+    #[test]
+    fn invalid_kernel() {
+        let kernel =
+            NeighborRule::custom(ndarray::arr2(&[[1, 1, 1], [1, 0, 1], [1, 1, 7]])).unwrap();
+        let rule = Rule::new(LifeRule::One(2), LifeRule::One(3), 1, kernel).unwrap();
+        let checkpoint = Checkpoint {
+            generation: 0,
+            algorithm: Algorithm::Std,
+            border: BorderRule::Constant,
+            board: GameOfLifeStd::new(Array2::zeros((3, 3)), rule).to_board(),
+            seed: None,
+        };
+        let mut bytes = Vec::new();
+        checkpoint.write(&mut bytes).unwrap();
+        assert_eq!(Checkpoint::read(bytes.as_slice()).unwrap(), checkpoint);
+
+        // A kernel edited to a negative weight is rejected when reading instead of panicking in the convolution.
+        let json = String::from_utf8(bytes).unwrap();
+        assert_eq!(json.matches('7').count(), 1);
+        let error = Checkpoint::read(json.replace('7', "-5").as_bytes()).unwrap_err();
+        assert!(
+            error.to_string().contains("must not be negative"),
+            "{error}"
+        );
+    }
+}
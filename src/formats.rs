@@ -0,0 +1,566 @@
+//! # Formats
+//! Contains ways to read initial fields and kernels from files and to save fields.
+
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+
+use ndarray::{s, Array2};
+
+use crate::gameoflife::{check_dimensions, NeighborRule, MAX_CELLS};
+
+/// Reads a PNG into a field, where pixels darker than `threshold` are alive with value `state`.
+/// The image's width and height become `numx` and `numy`.
+pub fn read_image<P: AsRef<Path>>(
+    path: P,
+    threshold: u8,
+    state: u8,
+) -> Result<Array2<u8>, png::DecodingError> {
+    let (pixels, width, height, channels) = decode_png(path)?;
+    Ok(field_from_pixels(
+        &pixels, width, height, channels, threshold, state,
+    ))
+}
+
+/// Reads a PNG into a density map for [`GameBuilder::density_map`](crate::builder::GameBuilder::density_map),
+/// where black is 1 and white is 0.
+pub fn read_density_map<P: AsRef<Path>>(path: P) -> Result<Array2<f64>, png::DecodingError> {
+    let (pixels, width, height, channels) = decode_png(path)?;
+    Ok(Array2::from_shape_fn((width, height), |(x, y)| {
+        let pixel = &pixels[(y * width + x) * channels..][..channels];
+        1. - luminance(pixel) as f64 / 255.
+    }))
+}
+
+/// Decodes a PNG into 8-bit pixels stored row by row and returns them with the width, height, and number of channels.
+fn decode_png<P: AsRef<Path>>(
+    path: P,
+) -> Result<(Vec<u8>, usize, usize, usize), png::DecodingError> {
+    let mut decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels)?;
+    pixels.truncate(info.buffer_size());
+
+    Ok((
+        pixels,
+        info.width as usize,
+        info.height as usize,
+        info.color_type.samples(),
+    ))
+}
+
+/// Converts 8-bit pixels stored row by row into a field, where pixels darker than `threshold` are alive with value `state`.
+/// - `channels`: 1 (grayscale), 2 (grayscale + alpha), 3 (RGB) or 4 (RGBA).
+pub fn field_from_pixels(
+    pixels: &[u8],
+    width: usize,
+    height: usize,
+    channels: usize,
+    threshold: u8,
+    state: u8,
+) -> Array2<u8> {
+    Array2::from_shape_fn((width, height), |(x, y)| {
+        let pixel = &pixels[(y * width + x) * channels..][..channels];
+        (luminance(pixel) < threshold as f32) as u8 * state
+    })
+}
+
+/// Returns the luminance of a grayscale or RGB pixel, ignoring the alpha channel.
+fn luminance(pixel: &[u8]) -> f32 {
+    if pixel.len() < 3 {
+        return pixel[0] as f32;
+    }
+    0.299 * pixel[0] as f32 + 0.587 * pixel[1] as f32 + 0.114 * pixel[2] as f32
+}
+
+/// Reads a plaintext (`.cells`) file into a field, where `O` is alive with value `state` and `.` is dead.
+/// Its longest row and its number of rows become `numx` and `numy`.
+pub fn read_plaintext<P: AsRef<Path>>(path: P, state: u8) -> io::Result<Array2<u8>> {
+    field_from_plaintext(&std::fs::read_to_string(path)?, state)
+}
+
+/// Saves a field as a plaintext (`.cells`) file, see [`plaintext_from_field`].
+pub fn write_plaintext<P: AsRef<Path>>(path: P, field: &Array2<u8>, state: u8) -> io::Result<()> {
+    std::fs::write(path, plaintext_from_field(field, state))
+}
+
+/// Converts the plaintext format into a field, where `O` is alive with value `state` and `.` is dead.
+/// Lines starting with `!` are comments and rows shorter than the longest one are filled with dead cells.
+pub fn field_from_plaintext(text: &str, state: u8) -> io::Result<Array2<u8>> {
+    let rows: Vec<&str> = text
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.starts_with('!'))
+        .collect();
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    if width == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the pattern contains no cells",
+        ));
+    }
+
+    let mut field = Array2::zeros((width, rows.len()));
+    for (y, row) in rows.iter().enumerate() {
+        for (x, cell) in row.chars().enumerate() {
+            field[[x, y]] = match cell {
+                'O' => state,
+                '.' => 0,
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("invalid cell '{cell}' in row {}", y + 1),
+                    ))
+                }
+            };
+        }
+    }
+    Ok(field)
+}
+
+/// Reasons why [`board_from_str`] cannot build a field.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseError {
+    Empty,
+    /// The character in the row (counted from 1) is neither alive nor dead.
+    InvalidCell {
+        cell: char,
+        row: usize,
+    },
+    /// The row (counted from 1) has a different length than the first one.
+    UnequalRow {
+        row: usize,
+        len: usize,
+        expected: usize,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            ParseError::Empty => write!(f, "the board contains no cells"),
+            ParseError::InvalidCell { cell, row } => {
+                write!(f, "invalid cell '{cell}' in row {row}")
+            }
+            ParseError::UnequalRow { row, len, expected } => write!(
+                f,
+                "row {row} has {len} cells, but the first one has {expected}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Builds a field from a grid with one line per row, which is handy for tests and quick experiments.
+/// `#`, `O` and `1` are alive with value `state`, `.`, ` ` and `0` are dead, and all rows need the same length.
+///
+/// ```
+/// use game_of_life::formats::board_from_str;
+///
+/// let glider = board_from_str(".#.\n..#\n###", 1).unwrap();
+/// assert_eq!(glider.dim(), (3, 3));
+/// assert_eq!(glider[[2, 1]], 1);
+/// ```
+pub fn board_from_str(s: &str, state: u8) -> Result<Array2<u8>, ParseError> {
+    let rows: Vec<&str> = s.lines().collect();
+    let width = rows.first().map_or(0, |row| row.chars().count());
+    if width == 0 {
+        return Err(ParseError::Empty);
+    }
+
+    let mut field = Array2::zeros((width, rows.len()));
+    for (y, row) in rows.iter().enumerate() {
+        let len = row.chars().count();
+        if len != width {
+            return Err(ParseError::UnequalRow {
+                row: y + 1,
+                len,
+                expected: width,
+            });
+        }
+        for (x, cell) in row.chars().enumerate() {
+            field[[x, y]] = match cell {
+                '#' | 'O' | '1' => state,
+                '.' | ' ' | '0' => 0,
+                _ => return Err(ParseError::InvalidCell { cell, row: y + 1 }),
+            };
+        }
+    }
+    Ok(field)
+}
+
+/// Converts a field into the plaintext format, where cells with value `state` are alive (`O`) and all others are dead (`.`).
+pub fn plaintext_from_field(field: &Array2<u8>, state: u8) -> String {
+    let mut text = String::from("!Generated by game-of-life\n");
+    for row in field.columns() {
+        text.extend(
+            row.iter()
+                .map(|&cell| if cell == state { 'O' } else { '.' }),
+        );
+        text.push('\n');
+    }
+    text
+}
+
+/// Reads a run length encoded (`.rle`) file into a field, see [`field_from_rle`].
+pub fn read_rle<P: AsRef<Path>>(path: P, state: u8) -> io::Result<Array2<u8>> {
+    field_from_rle(&std::fs::read_to_string(path)?, state)
+}
+
+/// Reads the rule of the header of a run length encoded (`.rle`) file, see [`rule_from_rle`].
+pub fn read_rle_rule<P: AsRef<Path>>(path: P) -> io::Result<Option<String>> {
+    Ok(rule_from_rle(&std::fs::read_to_string(path)?).map(str::to_owned))
+}
+
+/// Returns the rule of the `x = ..., y = ..., rule = ...` header of the run length encoded format as written, e.g. `B3/S23`,
+/// which can be parsed with [`Rule::from_str`](crate::gameoflife::Rule), or None if the header declares none.
+pub fn rule_from_rle(text: &str) -> Option<&str> {
+    // The rule comes last and can contain commas itself, e.g. `B3/S23:T10,10` for a torus.
+    let (_, rule) = rle_lines(text).next()?.split_once("rule")?;
+    Some(rule.trim_start().strip_prefix('=')?.trim())
+}
+
+/// Returns the trimmed lines of the run length encoded format without empty lines and comments, starting with the header.
+fn rle_lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// Converts the run length encoded format into a field, where `o` is alive with value `state` and `b` is dead.
+/// The size is taken from the `x = ..., y = ...` header, lines starting with `#` are comments, and the rule of the header is left to [`rule_from_rle`].
+/// Every cell as well as `$`, which ends a row, can be preceded by a count, and `!` ends the pattern.
+pub fn field_from_rle(text: &str, state: u8) -> io::Result<Array2<u8>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+    let mut lines = rle_lines(text);
+    let header = lines
+        .next()
+        .ok_or_else(|| invalid("the pattern has no header".into()))?;
+    let dimension = |name: &str| {
+        header
+            .split(',')
+            .filter_map(|entry| entry.split_once('='))
+            .find(|(key, _)| key.trim() == name)
+            .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+            .ok_or_else(|| invalid(format!("the header has no valid {name}")))
+    };
+    let (numx, numy) = (dimension("x")?, dimension("y")?);
+    if numx == 0 || numy == 0 {
+        return Err(invalid("the pattern contains no cells".into()));
+    }
+    check_dimensions(numx, numy, MAX_CELLS).map_err(|e| invalid(e.to_string()))?;
+
+    let mut field = Array2::zeros((numx, numy));
+    let (mut x, mut y) = (0usize, 0usize);
+    let mut count = 0usize;
+    let too_large = || invalid(format!("the pattern exceeds its size of {numx}x{numy}"));
+    for tag in lines.flat_map(str::chars) {
+        if let Some(digit) = tag.to_digit(10) {
+            count = count
+                .checked_mul(10)
+                .and_then(|count| count.checked_add(digit as usize))
+                .ok_or_else(too_large)?;
+            continue;
+        }
+        let run = count.max(1);
+        count = 0;
+        match tag {
+            'b' | '.' => x = x.checked_add(run).ok_or_else(too_large)?,
+            // Generations patterns use letters for the living states.
+            'o' | 'A'..='X' => {
+                let end = x.checked_add(run).ok_or_else(too_large)?;
+                if end > numx || y >= numy {
+                    return Err(too_large());
+                }
+                field.slice_mut(s![x..end, y]).fill(state);
+                x = end;
+            }
+            '$' => {
+                x = 0;
+                y = y.checked_add(run).ok_or_else(too_large)?;
+            }
+            '!' => break,
+            _ if tag.is_whitespace() => {}
+            _ => return Err(invalid(format!("invalid cell '{tag}' in row {}", y + 1))),
+        }
+    }
+    Ok(field)
+}
+
+/// Reads a Life 1.06 (`.lif`) file into a field, see [`field_from_life106`].
+pub fn read_life106<P: AsRef<Path>>(path: P, state: u8) -> io::Result<Array2<u8>> {
+    field_from_life106(&std::fs::read_to_string(path)?, state)
+}
+
+/// Parses the Life 1.06 format, a `#Life 1.06` header followed by one `x y` pair per living cell, into the set of living cells.
+/// The coordinates can be negative, and all other lines starting with `#` are comments.
+pub fn cells_from_life106(text: &str) -> io::Result<BTreeSet<(i64, i64)>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+    let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+    if lines.next() != Some("#Life 1.06") {
+        return Err(invalid("the pattern has no #Life 1.06 header".into()));
+    }
+    let mut cells = BTreeSet::new();
+    for line in lines.filter(|line| !line.starts_with('#')) {
+        let coordinates: Vec<_> = line.split_whitespace().map(str::parse::<i64>).collect();
+        match coordinates[..] {
+            [Ok(x), Ok(y)] => cells.insert((x, y)),
+            _ => return Err(invalid(format!("invalid coordinates '{line}'"))),
+        };
+    }
+    Ok(cells)
+}
+
+/// Converts the Life 1.06 format into a field that just fits the living cells with value `state`, see [`cells_from_life106`].
+/// The smallest coordinates become the origin, and patterns that are spread out too far for [`MAX_CELLS`] are rejected.
+pub fn field_from_life106(text: &str, state: u8) -> io::Result<Array2<u8>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+    let cells = cells_from_life106(text)?;
+    let bounds = |coordinate: fn(&(i64, i64)) -> i64| {
+        let min = cells.iter().map(coordinate).min()?;
+        let max = cells.iter().map(coordinate).max()?;
+        Some((min, max))
+    };
+    let (Some((min_x, max_x)), Some((min_y, max_y))) = (bounds(|&(x, _)| x), bounds(|&(_, y)| y))
+    else {
+        return Err(invalid("the pattern contains no cells".into()));
+    };
+    // The extent of coordinates far apart does not fit into an `i64`.
+    let extent = |min: i64, max: i64| usize::try_from(max as i128 - min as i128 + 1).ok();
+    let (numx, numy) = match (extent(min_x, max_x), extent(min_y, max_y)) {
+        (Some(numx), Some(numy)) => (numx, numy),
+        _ => return Err(invalid("the pattern is spread out too far".into())),
+    };
+    check_dimensions(numx, numy, MAX_CELLS).map_err(|e| invalid(e.to_string()))?;
+
+    let mut field = Array2::zeros((numx, numy));
+    for (x, y) in cells {
+        field[[(x - min_x) as usize, (y - min_y) as usize]] = state;
+    }
+    Ok(field)
+}
+
+/// Reads a kernel for [`NeighborRule::custom`] from a text file and checks that it is valid.
+/// See [`kernel_from_text`] for the format.
+pub fn read_kernel<P: AsRef<Path>>(path: P) -> io::Result<NeighborRule> {
+    let kernel = kernel_from_text(&std::fs::read_to_string(path)?)?;
+    NeighborRule::custom(kernel).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Converts rows of weights separated by commas or whitespace, e.g. a CSV file, into a kernel.
+/// Empty lines and lines starting with `#` are skipped. Rows become `y` and columns `x`, as in the field.
+pub fn kernel_from_text(text: &str) -> io::Result<Array2<i32>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+    let mut rows = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let row = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|weight| !weight.is_empty())
+            .map(|weight| {
+                weight
+                    .parse::<i32>()
+                    .map_err(|_| invalid(format!("invalid weight '{weight}'")))
+            })
+            .collect::<io::Result<Vec<i32>>>()?;
+        rows.push(row);
+    }
+
+    let width = rows.first().map_or(0, Vec::len);
+    if width == 0 {
+        return Err(invalid("the kernel contains no weights".into()));
+    }
+    if rows.iter().any(|row| row.len() != width) {
+        return Err(invalid(
+            "all rows of the kernel need the same length".into(),
+        ));
+    }
+    Ok(Array2::from_shape_fn((width, rows.len()), |(x, y)| {
+        rows[y][x]
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use ndarray::arr2;
+
+    use super::*;
+    use crate::gameoflife::*;
+
+    #[test]
+    fn field_from_rgb() {
+        // 3x2 image: black, white, red / dark gray, light gray, blue
+        let pixels = [
+            0, 0, 0, 255, 255, 255, 255, 0, 0, //
+            50, 50, 50, 200, 200, 200, 0, 0, 255,
+        ];
+
+        assert_eq!(
+            field_from_pixels(&pixels, 3, 2, 3, 128, 2),
+            arr2(&[[2, 2], [0, 0], [2, 2]])
+        );
+        assert_eq!(
+            field_from_pixels(&pixels, 3, 2, 3, 40, 1),
+            arr2(&[[1, 0], [0, 0], [0, 1]])
+        );
+    }
+
+    #[test]
+    fn plaintext() {
+        let field = arr2(&[[3, 0], [1, 3], [0, 0]]);
+        let text = plaintext_from_field(&field, 3);
+        assert_eq!(text, "!Generated by game-of-life\nO..\n.O.\n");
+
+        assert_eq!(
+            field_from_plaintext(&text, 3).unwrap(),
+            arr2(&[[3, 0], [0, 3], [0, 0]])
+        );
+        assert_eq!(
+            field_from_plaintext("!Name: Blinker\nOOO\n\n.O", 1).unwrap(),
+            arr2(&[[1, 0, 0], [1, 0, 1], [1, 0, 0]])
+        );
+        assert!(field_from_plaintext("!Empty\n", 1).is_err());
+        assert!(field_from_plaintext("O*O", 1).is_err());
+    }
+
+    #[test]
+    fn board() {
+        assert_eq!(
+            board_from_str(".#.\nO 0\n1..", 2).unwrap(),
+            arr2(&[[0, 2, 2], [2, 0, 0], [0, 0, 0]])
+        );
+        assert_eq!(board_from_str("", 1), Err(ParseError::Empty));
+        assert_eq!(
+            board_from_str("..\n.", 1),
+            Err(ParseError::UnequalRow {
+                row: 2,
+                len: 1,
+                expected: 2
+            })
+        );
+        assert_eq!(
+            board_from_str("..\n*.", 1),
+            Err(ParseError::InvalidCell { cell: '*', row: 2 })
+        );
+    }
+
+    #[test]
+    fn rle() {
+        let glider = "#N Glider\n#C A comment\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!";
+        assert_eq!(
+            field_from_rle(glider, 2).unwrap(),
+            arr2(&[[0, 0, 2], [2, 0, 2], [0, 2, 2]])
+        );
+        // Runs can span lines and skip rows.
+        assert_eq!(
+            field_from_rle("x=4,y=3\n2o\n2b2$\nbo!", 1).unwrap(),
+            arr2(&[[1, 0, 0], [1, 0, 1], [0, 0, 0], [0, 0, 0]])
+        );
+        for invalid in [
+            "",
+            "bo$!",
+            "x = 0, y = 1\n!",
+            "x = 2, y = 2\n3o!",
+            "x = 2, y = 2\nozo!",
+            // Too many cells to allocate.
+            "x = 100000000, y = 100000000\no!",
+            // Runs that overflow the count or the position.
+            "x = 2, y = 2\n99999999999999999999999o!",
+            "x = 2, y = 2\n18446744073709551615bo!",
+            "x = 2, y = 2\n18446744073709551615$18446744073709551615$o!",
+        ] {
+            let error = field_from_rle(invalid, 1).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn life106() {
+        let glider = "#Life 1.06\n#D A glider around the origin\n0 -1\n1 0\n-1 1\n0 1\n1 1\n";
+        assert_eq!(
+            cells_from_life106(glider).unwrap(),
+            BTreeSet::from([(0, -1), (1, 0), (-1, 1), (0, 1), (1, 1)])
+        );
+        assert_eq!(
+            field_from_life106(glider, 2).unwrap(),
+            arr2(&[[0, 0, 2], [2, 0, 2], [0, 2, 2]])
+        );
+
+        // Sparse cells far from the origin only take the space between them.
+        let field = field_from_life106("#Life 1.06\n-1000 5000\n-998 5003\n", 1).unwrap();
+        assert_eq!(field.dim(), (3, 4));
+        assert_eq!(field.sum(), 2);
+        assert_eq!((field[[0, 0]], field[[2, 3]]), (1, 1));
+
+        for invalid in [
+            "",
+            "0 0",
+            "#Life 1.05\n#P 0 0\n.*",
+            "#Life 1.06\n",
+            "#Life 1.06\n0 0 0",
+            "#Life 1.06\n0 x",
+            "#Life 1.06\n0 0\n5000 5000",
+            "#Life 1.06\n-9223372036854775808 0\n9223372036854775807 0",
+        ] {
+            assert!(field_from_life106(invalid, 1).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn rle_rule() {
+        assert_eq!(
+            rule_from_rle("x = 3, y = 3, rule = B3/S23\nbob!"),
+            Some("B3/S23")
+        );
+        assert_eq!(
+            rule_from_rle("x = 3, y = 3, rule = B3/S23:T10,10\nbob!"),
+            Some("B3/S23:T10,10")
+        );
+        assert_eq!(rule_from_rle("#C rule = B36/S23\nx = 3, y = 3\nbob!"), None);
+
+        // The replicator of HighLife turns into two copies of itself after 12 generations, which Conway's rule does not.
+        let replicator = "#N Replicator\nx = 5, y = 5, rule = B36/S23\n2b3o$bo2bo$o3bo$o2bo$3o!";
+        let rule = Rule::from_str(rule_from_rle(replicator).unwrap()).unwrap();
+        assert_ne!(rule, Rule::default());
+        let pattern = field_from_rle(replicator, rule.state as u8).unwrap();
+        let placed = |x: usize, y: usize| {
+            let mut field = Array2::zeros((20, 20));
+            field.slice_mut(s![x..x + 5, y..y + 5]).assign(&pattern);
+            field
+        };
+
+        let mut gol = GameOfLifeStd::new(placed(8, 8), rule);
+        let mut conway = GameOfLifeStd::new(placed(8, 8), Rule::default());
+        for _ in 0..12 {
+            gol.compute_next_generation();
+            conway.compute_next_generation();
+        }
+        let copies = placed(6, 6) | placed(10, 10);
+        assert_eq!(gol.snapshot(), copies);
+        assert_ne!(conway.snapshot(), copies);
+    }
+
+    #[test]
+    fn kernel() {
+        assert_eq!(
+            kernel_from_text("# weights\n1, 2, 3\n\n4 0 5\n6,7,  8\n").unwrap(),
+            arr2(&[[1, 4, 6], [2, 0, 7], [3, 5, 8]])
+        );
+        for invalid in ["", "1, 2\n3", "1, a, 2"] {
+            assert!(kernel_from_text(invalid).is_err(), "{invalid}");
+        }
+    }
+}
@@ -1,32 +1,232 @@
 //! # Game of Life
 //! Contains a collection of structures necessary for building a Game of Life.
 
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
 use std::ops::Range;
 use std::str::FromStr;
-use std::sync::atomic::Ordering;
-use std::{fmt::Display, sync::atomic::AtomicU8};
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "presentation")]
+use std::sync::Arc;
+
+use ndarray::{self, aview2, Array2, ArrayView2, CowArray, Ix2, ShapeError, Zip};
+use ndarray_ndimage::{convolve, BorderMode};
+use rand::Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Neighbor weights indexed by `[x, y]`, see [`NeighborRule::kernel`].
+const MOORE: [[i32; 3]; 3] = [[1, 1, 1], [1, 0, 1], [1, 1, 1]];
+const VON_NEUMANN: [[i32; 3]; 3] = [[0, 1, 0], [1, 0, 1], [0, 1, 0]];
+const HEX_EVEN: [[i32; 3]; 3] = [[1, 1, 1], [1, 0, 1], [0, 1, 0]];
+const HEX_ODD: [[i32; 3]; 3] = [[0, 1, 0], [1, 0, 1], [1, 1, 1]];
+
+/// Largest radius of [`NeighborRule::VonNeumannR`], so that its kernel of (2 * radius + 1)² cells fits into [`MAX_CELLS`].
+pub const MAX_RADIUS: usize = 2047;
+
+/// Value of wall cells in fields of `u8`, which never change and are never counted as living neighbors.
+/// The `state` of a rule therefore has to be smaller than `WALL`, see [`Cell::WALL`] for the other cell types.
+pub const WALL: u8 = u8::MAX;
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for u8 {}
+    impl Sealed for u16 {}
+    impl Sealed for u32 {}
+}
+
+/// Unsigned integer types the cells of [`GameOfLifeStd`] and [`GameOfLifeConvolution`] can have.
+/// `u8` suffices for up to 254 states, wider types allow more, e.g. for Generations rules with a long decay.
+pub trait Cell:
+    sealed::Sealed
+    + Copy
+    + Debug
+    + Default
+    + Eq
+    + Ord
+    + Hash
+    + Send
+    + Sync
+    + From<u8>
+    + Into<u32>
+    + TryFrom<u32>
+    + 'static
+{
+    /// Value of wall cells, the largest value of the type.
+    const WALL: Self;
+
+    /// Returns the value one lower, which is how living cells decay, but at least 0.
+    fn decay(self) -> Self;
+}
+
+macro_rules! impl_cell {
+    ($($t:ty),*) => {
+        $(
+            impl Cell for $t {
+                const WALL: Self = <$t>::MAX;
+
+                fn decay(self) -> Self {
+                    self.saturating_sub(1)
+                }
+            }
+        )*
+    };
+}
 
-use ndarray::{self, arr2, s, Array2, Zip};
-use ndarray_ndimage::convolve;
+impl_cell!(u8, u16, u32);
 
 /// Possible rules about which cells count as neighbors.
-#[derive(Clone)]
+/// - `Hex`: The six neighbors on a hexagonal grid, where odd rows are shifted half a cell to the right.
+/// - `VonNeumannR`: All cells within the given Manhattan distance, i.e. a diamond of that radius.
+///   Only the algorithms on the CPU support it.
+/// - `Custom`: Living cells are weighted by a kernel indexed by `[x, y]` and centered on the cell, see [`NeighborRule::custom`].
+///   Only the algorithms on the CPU support it.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum NeighborRule {
     Moore,
     VonNeumann,
+    Hex,
+    VonNeumannR(usize),
+    Custom(Kernel),
+}
+
+/// Weights of the neighbors of [`NeighborRule::Custom`], indexed by `[x, y]` and centered on the cell.
+/// Every kernel went through the checks of [`Kernel::new`], including deserialized ones.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "Array2<i32>", into = "Array2<i32>")
+)]
+pub struct Kernel(Array2<i32>);
+
+impl Kernel {
+    /// Checks that the kernel is square with an odd size, has non-negative weights that add up to at most [`MAX_CELLS`],
+    /// and a zero center.
+    pub fn new(weights: Array2<i32>) -> Result<Self, KernelError> {
+        let (width, height) = weights.dim();
+        if width != height || width % 2 == 0 {
+            return Err(KernelError::Shape(width, height));
+        }
+        if weights.iter().any(|&weight| weight < 0) {
+            return Err(KernelError::NegativeWeight);
+        }
+        let sum: u64 = weights.iter().map(|&weight| weight as u64).sum();
+        if sum > MAX_CELLS as u64 {
+            return Err(KernelError::TooManyNeighbors(sum));
+        }
+        if weights[[width / 2, height / 2]] != 0 {
+            return Err(KernelError::NonZeroCenter);
+        }
+        Ok(Self(weights))
+    }
+
+    pub fn weights(&self) -> ArrayView2<'_, i32> {
+        self.0.view()
+    }
+}
+
+impl TryFrom<Array2<i32>> for Kernel {
+    type Error = KernelError;
+
+    fn try_from(weights: Array2<i32>) -> Result<Self, Self::Error> {
+        Self::new(weights)
+    }
+}
+
+impl From<Kernel> for Array2<i32> {
+    fn from(kernel: Kernel) -> Self {
+        kernel.0
+    }
+}
+
+impl NeighborRule {
+    /// Wraps the kernel into `NeighborRule::Custom` after the checks of [`Kernel::new`].
+    pub fn custom(kernel: Array2<i32>) -> Result<NeighborRule, KernelError> {
+        Ok(NeighborRule::Custom(Kernel::new(kernel)?))
+    }
+
+    /// Returns the weights of the neighbors of a cell in row `y`, indexed by `[x, y]` and centered on the cell.
+    /// Only the hexagonal kernel depends on whether the row is even or odd.
+    pub fn kernel(&self, y: usize) -> CowArray<'_, i32, Ix2> {
+        match self {
+            NeighborRule::Moore => aview2(&MOORE).into(),
+            NeighborRule::VonNeumann => aview2(&VON_NEUMANN).into(),
+            NeighborRule::Hex if y % 2 == 1 => aview2(&HEX_ODD).into(),
+            NeighborRule::Hex => aview2(&HEX_EVEN).into(),
+            NeighborRule::VonNeumannR(radius) => {
+                let size = 2 * radius + 1;
+                let offset = |i: usize| i as isize - *radius as isize;
+                Array2::from_shape_fn((size, size), |(x, y)| self.weight(offset(x), offset(y), 0))
+                    .into()
+            }
+            NeighborRule::Custom(kernel) => kernel.weights().into(),
+        }
+    }
+
+    /// Returns how far the neighbors reach from the cell, i.e. half the size of the kernel.
+    pub fn radius(&self) -> usize {
+        match self {
+            NeighborRule::VonNeumannR(radius) => *radius,
+            NeighborRule::Custom(kernel) => kernel.0.nrows() / 2,
+            _ => 1,
+        }
+    }
+
+    /// Returns the weight of the neighbor at the offset (dx, dy) from a cell in row `y`.
+    /// The diamond is computed directly, so that counting the neighbors of a cell does not build its kernel.
+    fn weight(&self, dx: isize, dy: isize, y: usize) -> i32 {
+        match self {
+            NeighborRule::VonNeumannR(radius) => {
+                i32::from((dx, dy) != (0, 0) && dx.unsigned_abs() + dy.unsigned_abs() <= *radius)
+            }
+            _ => {
+                let radius = self.radius() as isize;
+                self.kernel(y)[[(dx + radius) as usize, (dy + radius) as usize]]
+            }
+        }
+    }
+
+    /// Returns the highest possible number of living neighbors, i.e. the sum of the weights for custom kernels.
+    /// Saturates at `usize::MAX` for radii far beyond [`MAX_RADIUS`], which [`check_neighbor`] rejects.
+    pub fn max_neighbors(&self) -> usize {
+        match self {
+            NeighborRule::Moore => 8,
+            NeighborRule::VonNeumann => 4,
+            NeighborRule::Hex => 6,
+            NeighborRule::VonNeumannR(radius) => radius
+                .checked_add(1)
+                .and_then(|next| next.checked_mul(*radius))
+                .and_then(|neighbors| neighbors.checked_mul(2))
+                .unwrap_or(usize::MAX),
+            NeighborRule::Custom(kernel) => kernel.0.iter().map(|&weight| weight as usize).sum(),
+        }
+    }
 }
 
 impl FromStr for NeighborRule {
     type Err = ();
 
+    /// Besides the names, parses `vn` followed by a radius of at most [`MAX_RADIUS`], e.g. `vn2`, as [`NeighborRule::VonNeumannR`].
     fn from_str(input: &str) -> Result<NeighborRule, Self::Err> {
-        match input.to_lowercase().as_str() {
+        let input = input.to_lowercase();
+        match input.as_str() {
             "m" => Ok(NeighborRule::Moore),
             "moore" => Ok(NeighborRule::Moore),
             "v" => Ok(NeighborRule::VonNeumann),
             "vn" => Ok(NeighborRule::VonNeumann),
             "vonneumann" => Ok(NeighborRule::VonNeumann),
-            _ => Err(()),
+            "h" => Ok(NeighborRule::Hex),
+            "hex" => Ok(NeighborRule::Hex),
+            "hexagonal" => Ok(NeighborRule::Hex),
+            _ => input
+                .strip_prefix("vn")
+                .and_then(|radius| radius.parse().ok())
+                .filter(|&radius| radius <= MAX_RADIUS)
+                .map(NeighborRule::VonNeumannR)
+                .ok_or(()),
         }
     }
 }
@@ -36,182 +236,928 @@ impl Display for NeighborRule {
         match *self {
             NeighborRule::Moore => write!(f, "Moore"),
             NeighborRule::VonNeumann => write!(f, "von Neumann"),
+            NeighborRule::Hex => write!(f, "hexagonal"),
+            NeighborRule::VonNeumannR(radius) => write!(f, "von Neumann radius {radius}"),
+            NeighborRule::Custom(ref kernel) => {
+                write!(f, "custom {}x{}", kernel.0.nrows(), kernel.0.ncols())
+            }
+        }
+    }
+}
+
+/// Reasons why a kernel cannot be used by [`NeighborRule::custom`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum KernelError {
+    /// The kernel is not square with an odd size.
+    Shape(usize, usize),
+    NegativeWeight,
+    /// The weights add up to more than [`MAX_CELLS`].
+    TooManyNeighbors(u64),
+    NonZeroCenter,
+}
+
+impl Display for KernelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KernelError::Shape(width, height) => write!(
+                f,
+                "the kernel has to be square with an odd size, but is {width}x{height}"
+            ),
+            KernelError::NegativeWeight => write!(f, "the weights must not be negative"),
+            KernelError::TooManyNeighbors(sum) => write!(
+                f,
+                "the weights can add up to at most {MAX_CELLS}, but add up to {sum}"
+            ),
+            KernelError::NonZeroCenter => write!(f, "the center of the kernel has to be 0"),
+        }
+    }
+}
+
+impl std::error::Error for KernelError {}
+
+/// Possible rules about how the field is extended beyond its borders.
+/// `GameOfLifeStd` only supports `Constant`, the others are exclusive to `GameOfLifeConvolution`.
+/// - `Constant`: All cells outside the field are dead.
+/// - `Nearest`: Replicates the outermost cells.
+/// - `Mirror`: Reflects about the center of the outermost cells.
+/// - `Reflect`: Reflects about the edge of the outermost cells.
+/// - `Wrap`: Wraps around to the opposite edge (torus).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BorderRule {
+    #[default]
+    Constant,
+    Nearest,
+    Mirror,
+    Reflect,
+    Wrap,
+}
+
+impl BorderRule {
+    /// Maps a possibly outside index to the cell of a row or column of `len` cells it stands for, or None for `Constant`.
+    fn index(self, index: isize, len: usize) -> Option<usize> {
+        let len = len as isize;
+        if (0..len).contains(&index) {
+            return Some(index as usize);
+        }
+        let index = match self {
+            BorderRule::Constant => return None,
+            BorderRule::Nearest => index.clamp(0, len - 1),
+            BorderRule::Mirror if len == 1 => 0,
+            BorderRule::Mirror => {
+                let period = 2 * len - 2;
+                let index = index.rem_euclid(period);
+                index.min(period - index)
+            }
+            BorderRule::Reflect => {
+                let index = index.rem_euclid(2 * len);
+                index.min(2 * len - 1 - index)
+            }
+            BorderRule::Wrap => index.rem_euclid(len),
+        };
+        Some(index as usize)
+    }
+}
+
+/// Yields the positions of the neighbors of the cell at (x, y) in a field of `numx` x `numy` cells.
+///
+/// Neighbors outside the field are left out for `BorderRule::Constant` and mapped into the field otherwise,
+/// so for the other border rules, a position can appear more than once or be the cell itself.
+/// Custom kernels yield every position with a non-zero weight.
+///
+/// ```
+/// use game_of_life::gameoflife::*;
+///
+/// let corner: Vec<_> = neighbor_coords(0, 0, 5, 5, &NeighborRule::VonNeumann, BorderRule::Constant).collect();
+/// assert_eq!(corner, [(0, 1), (1, 0)]);
+/// let wrapped: Vec<_> = neighbor_coords(0, 0, 5, 5, &NeighborRule::VonNeumann, BorderRule::Wrap).collect();
+/// assert_eq!(wrapped, [(4, 0), (0, 4), (0, 1), (1, 0)]);
+/// ```
+pub fn neighbor_coords(
+    x: usize,
+    y: usize,
+    numx: usize,
+    numy: usize,
+    neighbor: &NeighborRule,
+    border: BorderRule,
+) -> impl Iterator<Item = (usize, usize)> + '_ {
+    weighted_neighbors(x, y, numx, numy, neighbor, border).map(|(position, _)| position)
+}
+
+/// Like [`neighbor_coords`], but also yields the weight of every neighbor.
+fn weighted_neighbors(
+    x: usize,
+    y: usize,
+    numx: usize,
+    numy: usize,
+    neighbor: &NeighborRule,
+    border: BorderRule,
+) -> impl Iterator<Item = ((usize, usize), i32)> + '_ {
+    let radius = neighbor.radius() as isize;
+    (-radius..=radius)
+        .flat_map(move |dx| (-radius..=radius).map(move |dy| (dx, dy)))
+        .filter_map(move |(dx, dy)| {
+            let weight = neighbor.weight(dx, dy, y);
+            if weight == 0 {
+                return None;
+            }
+            let nx = border.index(x as isize + dx, numx)?;
+            let ny = border.index(y as isize + dy, numy)?;
+            Some(((nx, ny), weight))
+        })
+}
+
+impl BorderRule {
+    /// Returns the corresponding `ndarray_ndimage` border mode.
+    fn into_border_mode(self) -> BorderMode<usize> {
+        match self {
+            BorderRule::Constant => BorderMode::Constant(0),
+            BorderRule::Nearest => BorderMode::Nearest,
+            BorderRule::Mirror => BorderMode::Mirror,
+            BorderRule::Reflect => BorderMode::Reflect,
+            BorderRule::Wrap => BorderMode::Wrap,
+        }
+    }
+}
+
+impl FromStr for BorderRule {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<BorderRule, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "c" => Ok(BorderRule::Constant),
+            "constant" => Ok(BorderRule::Constant),
+            "n" => Ok(BorderRule::Nearest),
+            "nearest" => Ok(BorderRule::Nearest),
+            "m" => Ok(BorderRule::Mirror),
+            "mirror" => Ok(BorderRule::Mirror),
+            "r" => Ok(BorderRule::Reflect),
+            "reflect" => Ok(BorderRule::Reflect),
+            "w" => Ok(BorderRule::Wrap),
+            "wrap" => Ok(BorderRule::Wrap),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for BorderRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            BorderRule::Constant => write!(f, "constant"),
+            BorderRule::Nearest => write!(f, "nearest"),
+            BorderRule::Mirror => write!(f, "mirror"),
+            BorderRule::Reflect => write!(f, "reflect"),
+            BorderRule::Wrap => write!(f, "wrap"),
         }
     }
 }
 
 /// Comfortable interfaces to create rules for survival and birth.
+/// - `All`: Any number of living neighbors, including none.
+/// - `None`: Never, e.g. for rules without survival.
 #[derive(Clone)]
 pub enum LifeRule<'a> {
     One(usize),
     Range(Range<usize>),
     Numbers(&'a [usize]),
     Raw([bool; 9]),
+    All,
+    None,
 }
 
 impl<'a> LifeRule<'a> {
-    /// Returns the raw boolean array.
-    fn into_array(self) -> [bool; 9] {
-        let mut return_array = [false; 9];
-        match self {
-            LifeRule::One(one) => return_array[one] = true,
-            LifeRule::Range(range) => {
-                for i in range {
-                    return_array[i] = true;
-                }
-            }
-            LifeRule::Numbers(array) => {
-                for i in array {
-                    return_array[*i] = true;
-                }
+    /// Returns the raw boolean array indexed by the number of living neighbors from 0 to `max_neighbors`.
+    /// Returns an error if a number is larger than `max_neighbors`, see [`NeighborRule::max_neighbors`].
+    ///
+    /// ```
+    /// use game_of_life::gameoflife::*;
+    ///
+    /// let array = LifeRule::Range(1..3).try_into_array(4).unwrap();
+    /// assert_eq!(array, [false, true, true, false, false]);
+    /// assert!(LifeRule::One(5).try_into_array(4).is_err());
+    /// ```
+    pub fn try_into_array(self, max_neighbors: usize) -> Result<Vec<bool>, GolError> {
+        let mut return_array = vec![false; max_neighbors + 1];
+        let mut set = |count: usize| match return_array.get_mut(count) {
+            Some(entry) => {
+                *entry = true;
+                Ok(())
             }
-            LifeRule::Raw(array) => return_array = array,
-        }
-        return_array
+            None => Err(GolError::InvalidNeighborCount {
+                count,
+                max_neighbors,
+            }),
+        };
+        match self {
+            LifeRule::One(one) => set(one),
+            LifeRule::Range(mut range) => range.try_for_each(set),
+            LifeRule::Numbers(array) => array.iter().copied().try_for_each(set),
+            LifeRule::Raw(array) => (0..9).filter(|&i| array[i]).try_for_each(set),
+            LifeRule::All => (0..=max_neighbors).try_for_each(set),
+            LifeRule::None => Ok(()),
+        }?;
+        Ok(return_array)
     }
 }
 
 /// Rule of a Game of Life.
-/// - `survival`: With how many neighbors a living cell survives.
-/// - `birth`: With how many neighbors a dead cell is born.
-/// - `state`: After how many iterations a cell dies.
+/// - `survival`: With how many neighbors a living cell survives, indexed by the number of neighbors.
+/// - `birth`: With how many neighbors a dead cell is born, indexed by the number of neighbors.
+/// - `state`: After how many iterations a cell dies, which has to be smaller than the [`Cell::WALL`] of the cell type.
 /// - `neighbor`: Neighbor counting algorithm.
-#[derive(Clone)]
+/// - `include_center`: Whether a living cell counts itself, i.e. the rule is totalistic instead of outer totalistic, see [`Rule::totalistic`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Rule {
-    pub survival: [bool; 9],
-    pub birth: [bool; 9],
-    pub state: u8,
+    pub survival: Vec<bool>,
+    pub birth: Vec<bool>,
+    pub state: u32,
     pub neighbor: NeighborRule,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub include_center: bool,
 }
 
 impl Rule {
-    pub fn new(survival: LifeRule, birth: LifeRule, state: u8, neighbor: NeighborRule) -> Self {
-        Self {
-            survival: survival.into_array(),
-            birth: birth.into_array(),
+    /// The survival and birth arrays cover at least 0 to 8 neighbors, or up to [`NeighborRule::max_neighbors`] if that is more.
+    /// Returns an error if survival or birth need more living neighbors than the neighbor rule can count,
+    /// or if the von Neumann radius is larger than [`MAX_RADIUS`].
+    ///
+    /// ```
+    /// use game_of_life::gameoflife::*;
+    ///
+    /// let rule = Rule::new(LifeRule::Numbers(&[2, 3]), LifeRule::One(3), 1, NeighborRule::Moore);
+    /// assert_eq!(rule, Ok(Rule::default()));
+    ///
+    /// let rule = Rule::new(LifeRule::One(2), LifeRule::One(5), 1, NeighborRule::VonNeumann);
+    /// assert_eq!(
+    ///     rule,
+    ///     Err(GolError::InvalidNeighborCount { count: 5, max_neighbors: 4 })
+    /// );
+    /// ```
+    pub fn new(
+        survival: LifeRule,
+        birth: LifeRule,
+        state: u32,
+        neighbor: NeighborRule,
+    ) -> Result<Self, GolError> {
+        Self::with_center(survival, birth, state, neighbor, false)
+    }
+
+    /// Like [`Rule::new`], but a living cell counts itself as one of its living neighbors, so the arrays have one more slot.
+    /// A totalistic rule that survives with one more neighbor behaves like the outer totalistic one, e.g. `B3/S34` is `B3/S23`.
+    ///
+    /// ```
+    /// use game_of_life::gameoflife::*;
+    ///
+    /// let rule = Rule::totalistic(LifeRule::Range(3..5), LifeRule::One(3), 1, NeighborRule::Moore).unwrap();
+    /// assert_eq!(rule.max_count(), 9);
+    /// assert!(Rule::totalistic(LifeRule::One(9), LifeRule::None, 1, NeighborRule::Moore).is_ok());
+    /// ```
+    pub fn totalistic(
+        survival: LifeRule,
+        birth: LifeRule,
+        state: u32,
+        neighbor: NeighborRule,
+    ) -> Result<Self, GolError> {
+        Self::with_center(survival, birth, state, neighbor, true)
+    }
+
+    fn with_center(
+        survival: LifeRule,
+        birth: LifeRule,
+        state: u32,
+        neighbor: NeighborRule,
+        include_center: bool,
+    ) -> Result<Self, GolError> {
+        check_neighbor(&neighbor)?;
+        let max_count = neighbor.max_neighbors() + include_center as usize;
+        let array = |life_rule: LifeRule| {
+            let mut array = life_rule.try_into_array(max_count)?;
+            array.resize(max_count.max(8) + 1, false);
+            Ok(array)
+        };
+        Ok(Self {
+            survival: array(survival)?,
+            birth: array(birth)?,
             state,
             neighbor,
+            include_center,
+        })
+    }
+
+    /// Returns the highest possible count of living cells, which includes the cell itself if `include_center` is set.
+    pub fn max_count(&self) -> usize {
+        self.neighbor.max_neighbors() + self.include_center as usize
+    }
+
+    /// Whether a living cell with `count` neighbors survives.
+    pub fn survives(&self, count: usize) -> bool {
+        self.survival.get(count).copied().unwrap_or(false)
+    }
+
+    /// Whether a dead cell with `count` neighbors is born.
+    pub fn is_born(&self, count: usize) -> bool {
+        self.birth.get(count).copied().unwrap_or(false)
+    }
+}
+
+impl FromStr for Rule {
+    type Err = ();
+
+    /// Parses a rulestring with the Moore neighbor rule.
+    /// - B/S notation: `B3/S23`, optionally with the number of states of the Generations family as in `B2/S/C3`.
+    /// - S/B notation: `23/3`, optionally with the number of states as in `/2/3`.
+    ///
+    /// The number of states C counts the dead state as well, so `state` is C - 1.
+    /// Fields of `u8` only have room for 255 states, wider cell types allow more, see [`Cell`].
+    fn from_str(input: &str) -> Result<Rule, Self::Err> {
+        /// Parses a string of neighbor counts like "23".
+        fn parse_counts(input: &str) -> Result<[bool; 9], ()> {
+            let mut counts = [false; 9];
+            for digit in input.chars() {
+                match digit.to_digit(10) {
+                    Some(count) if count <= 8 => counts[count as usize] = true,
+                    _ => return Err(()),
+                }
+            }
+            Ok(counts)
+        }
+
+        /// Parses the number of states C into the state of a living cell.
+        fn parse_states(input: &str) -> Result<u32, ()> {
+            match input.parse::<u32>() {
+                Ok(states) if states >= 2 => Ok(states - 1),
+                _ => Err(()),
+            }
+        }
+
+        let parts: Vec<&str> = input.trim().split('/').collect();
+        if !(2..=3).contains(&parts.len()) {
+            return Err(());
         }
+
+        let mut survival = None;
+        let mut birth = None;
+        let mut state = None;
+        if parts
+            .iter()
+            .any(|part| part.starts_with(char::is_alphabetic))
+        {
+            for part in parts {
+                let mut chars = part.chars();
+                match chars.next().map(|prefix| prefix.to_ascii_uppercase()) {
+                    Some('B') if birth.is_none() => birth = Some(parse_counts(chars.as_str())?),
+                    Some('S') if survival.is_none() => {
+                        survival = Some(parse_counts(chars.as_str())?)
+                    }
+                    Some('C' | 'G') if state.is_none() => {
+                        state = Some(parse_states(chars.as_str())?)
+                    }
+                    _ => return Err(()),
+                }
+            }
+        } else {
+            survival = Some(parse_counts(parts[0])?);
+            birth = Some(parse_counts(parts[1])?);
+            if let Some(states) = parts.get(2) {
+                state = Some(parse_states(states)?);
+            }
+        }
+
+        Rule::new(
+            LifeRule::Raw(survival.ok_or(())?),
+            LifeRule::Raw(birth.ok_or(())?),
+            state.unwrap_or(1),
+            NeighborRule::Moore,
+        )
+        .map_err(|_| ())
+    }
+}
+
+impl Display for Rule {
+    /// Writes the canonical B/S rulestring with sorted counts, e.g. `B36/S23`, and the number of states if there are more than two, e.g. `B2/S/C3`.
+    /// The neighbor rule is not part of the rulestring, and counts above 9 cannot be parsed back.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let counts = |array: &[bool]| -> String {
+            (0..array.len())
+                .filter(|&count| array[count])
+                .map(|count| count.to_string())
+                .collect()
+        };
+        write!(f, "B{}/S{}", counts(&self.birth), counts(&self.survival))?;
+        if self.state > 1 {
+            write!(f, "/C{}", self.state as u64 + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Well-known rules with the Moore neighbor rule by name, see [`Rule::name`].
+const NAMED_RULES: [(&str, &str); 12] = [
+    ("Conway's Life", "B3/S23"),
+    ("HighLife", "B36/S23"),
+    ("Seeds", "B2/S"),
+    ("Day & Night", "B3678/S34678"),
+    ("Life without Death", "B3/S012345678"),
+    ("Replicator", "B1357/S1357"),
+    ("Diamoeba", "B35678/S5678"),
+    ("2x2", "B36/S125"),
+    ("Morley", "B368/S245"),
+    ("Maze", "B3/S12345"),
+    ("Brian's Brain", "B2/S/C3"),
+    ("Star Wars", "B2/S345/C4"),
+];
+
+impl Rule {
+    /// Returns the name of a well-known rule, e.g. `Conway's Life` for `B3/S23`.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use game_of_life::gameoflife::Rule;
+    ///
+    /// assert_eq!(Rule::from_str("s23/b36").unwrap().name(), Some("HighLife"));
+    /// assert_eq!(Rule::from_str("B3/S2").unwrap().name(), None);
+    /// ```
+    pub fn name(&self) -> Option<&'static str> {
+        NAMED_RULES
+            .iter()
+            .find(|(_, rulestring)| Rule::from_str(rulestring).as_ref() == Ok(self))
+            .map(|&(name, _)| name)
     }
 }
 
 impl Default for Rule {
     fn default() -> Self {
         Self {
-            survival: [false, false, true, true, false, false, false, false, false],
-            birth: [false, false, false, true, false, false, false, false, false],
+            survival: vec![false, false, true, true, false, false, false, false, false],
+            birth: vec![false, false, false, true, false, false, false, false, false],
             state: 1,
             neighbor: NeighborRule::Moore,
+            include_center: false,
+        }
+    }
+}
+
+/// Snapshot of a Game of Life independent of the algorithm.
+/// - `cells`: The values of the field in row-major order of the `(numx, numy)` shape.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Board<T = u8> {
+    pub numx: usize,
+    pub numy: usize,
+    pub cells: Vec<T>,
+    pub rule: Rule,
+}
+
+/// Default for the maximum number of cells of a field, e.g. 4096x4096.
+pub const MAX_CELLS: usize = 4096 * 4096;
+
+/// Errors of a Game of Life.
+#[derive(Clone, Debug)]
+pub enum GolError {
+    /// The field has no cells or more than `max_cells`.
+    InvalidDimensions {
+        numx: usize,
+        numy: usize,
+        max_cells: usize,
+    },
+    /// The rule needs more living neighbors than the neighbor rule can count.
+    InvalidNeighborCount { count: usize, max_neighbors: usize },
+    /// The state of the rule is not smaller than the wall of the cell type, see [`Cell::WALL`].
+    InvalidState { state: u32, wall: u32 },
+    /// The radius of [`NeighborRule::VonNeumannR`] is larger than [`MAX_RADIUS`].
+    InvalidRadius { radius: usize, max_radius: usize },
+    /// The frames of a GIF would be larger than 65535 pixels in either direction.
+    FrameTooLarge { width: usize, height: usize },
+    /// Writing a presentation failed, with the underlying error as the source.
+    #[cfg(feature = "presentation")]
+    Encoding(Arc<gif::EncodingError>),
+    /// Computing a generation panicked during a presentation, with the message of the panic.
+    Panicked(String),
+}
+
+impl Display for GolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            GolError::InvalidDimensions { numx, numy, .. } if numx == 0 || numy == 0 => {
+                write!(f, "A field needs at least one cell, but has {numx}x{numy}")
+            }
+            GolError::InvalidDimensions {
+                numx,
+                numy,
+                max_cells,
+            } => write!(
+                f,
+                "A field can have at most {max_cells} cells, but has {numx}x{numy}"
+            ),
+            GolError::InvalidNeighborCount {
+                count,
+                max_neighbors,
+            } => write!(
+                f,
+                "A cell can have at most {max_neighbors} living neighbors, but the rule needs {count}"
+            ),
+            GolError::InvalidState { state, wall } => write!(
+                f,
+                "The state has to be smaller than {wall} for the type of the cells, but is {state}"
+            ),
+            GolError::InvalidRadius { radius, max_radius } => write!(
+                f,
+                "The von Neumann radius can be at most {max_radius}, but is {radius}"
+            ),
+            GolError::FrameTooLarge { width, height } => write!(
+                f,
+                "A GIF can have at most {}x{} pixels, but the frames would have {width}x{height}; use a smaller field or scale",
+                u16::MAX,
+                u16::MAX
+            ),
+            #[cfg(feature = "presentation")]
+            GolError::Encoding(ref e) => write!(f, "Could not write the output: {e}"),
+            GolError::Panicked(ref message) => {
+                write!(f, "A generation could not be computed: {message}")
+            }
+        }
+    }
+}
+
+impl PartialEq for GolError {
+    /// Encoding errors cannot be compared, so they are only equal to clones of themselves.
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                GolError::InvalidDimensions {
+                    numx,
+                    numy,
+                    max_cells,
+                },
+                GolError::InvalidDimensions {
+                    numx: other_numx,
+                    numy: other_numy,
+                    max_cells: other_max_cells,
+                },
+            ) => (numx, numy, max_cells) == (other_numx, other_numy, other_max_cells),
+            (
+                GolError::InvalidNeighborCount {
+                    count,
+                    max_neighbors,
+                },
+                GolError::InvalidNeighborCount {
+                    count: other_count,
+                    max_neighbors: other_max_neighbors,
+                },
+            ) => (count, max_neighbors) == (other_count, other_max_neighbors),
+            (
+                GolError::InvalidState { state, wall },
+                GolError::InvalidState {
+                    state: other_state,
+                    wall: other_wall,
+                },
+            ) => (state, wall) == (other_state, other_wall),
+            (
+                GolError::InvalidRadius { radius, max_radius },
+                GolError::InvalidRadius {
+                    radius: other_radius,
+                    max_radius: other_max_radius,
+                },
+            ) => (radius, max_radius) == (other_radius, other_max_radius),
+            (
+                GolError::FrameTooLarge { width, height },
+                GolError::FrameTooLarge {
+                    width: other_width,
+                    height: other_height,
+                },
+            ) => (width, height) == (other_width, other_height),
+            #[cfg(feature = "presentation")]
+            (GolError::Encoding(e), GolError::Encoding(other)) => Arc::ptr_eq(e, other),
+            (GolError::Panicked(message), GolError::Panicked(other)) => message == other,
+            _ => false,
+        }
+    }
+}
+
+impl std::error::Error for GolError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            #[cfg(feature = "presentation")]
+            GolError::Encoding(ref e) => Some(e.as_ref()),
+            _ => None,
         }
     }
 }
 
+/// Checks that a field with `numx` x `numy` cells is not empty and has at most `max_cells` cells.
+pub fn check_dimensions(numx: usize, numy: usize, max_cells: usize) -> Result<(), GolError> {
+    let cells = numx.checked_mul(numy);
+    if numx == 0 || numy == 0 || cells.is_none_or(|cells| cells > max_cells) {
+        return Err(GolError::InvalidDimensions {
+            numx,
+            numy,
+            max_cells,
+        });
+    }
+    Ok(())
+}
+
+/// Checks that the radius of [`NeighborRule::VonNeumannR`] is at most [`MAX_RADIUS`].
+/// Custom kernels need no check here, as every [`Kernel`] was checked when it was built.
+pub fn check_neighbor(neighbor: &NeighborRule) -> Result<(), GolError> {
+    match *neighbor {
+        NeighborRule::VonNeumannR(radius) if radius > MAX_RADIUS => Err(GolError::InvalidRadius {
+            radius,
+            max_radius: MAX_RADIUS,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that the state fits into the cell type `T` below its [`Cell::WALL`] and converts it.
+///
+/// ```
+/// use game_of_life::gameoflife::*;
+///
+/// assert_eq!(check_state::<u8>(254), Ok(254));
+/// assert!(check_state::<u8>(255).is_err());
+/// assert_eq!(check_state::<u16>(255), Ok(255));
+/// ```
+pub fn check_state<T: Cell>(state: u32) -> Result<T, GolError> {
+    let wall: u32 = T::WALL.into();
+    if state >= wall {
+        return Err(GolError::InvalidState { state, wall });
+    }
+    T::try_from(state).map_err(|_| GolError::InvalidState { state, wall })
+}
+
 /// Trait to generalize possible Game of Life algorithms.
 pub trait GameOfLife {
-    type Data;
+    type Data: Cell;
 
     /// Generate a new Game of Life from an initial field.
+    ///
+    /// # Panics
+    /// Panics if the field has no cells, see [`check_dimensions`], if the state does not fit into the cell type, see [`check_state`],
+    /// or if the von Neumann radius is too large, see [`check_neighbor`].
     fn new(field: Array2<Self::Data>, rules: Rule) -> Self;
 
-    /// Compute the next generation.
-    fn compute_next_generation(&mut self);
+    /// Generate a new Game of Life from a snapshot.
+    /// Returns an error if the number of cells does not match the dimensions.
+    fn from_board(board: Board<Self::Data>) -> Result<Self, ShapeError>
+    where
+        Self: Sized,
+    {
+        let field = Array2::from_shape_vec((board.numx, board.numy), board.cells)?;
+        Ok(Self::new(field, board.rule))
+    }
+
+    /// Generate a new Game of Life where each cell is alive with `probability`.
+    ///
+    /// # Panics
+    /// Panics if the field has no cells or `probability` is not between 0 and 1.
+    fn random<R: Rng + ?Sized>(
+        numx: usize,
+        numy: usize,
+        rules: Rule,
+        probability: f64,
+        rng: &mut R,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        let state = check_state::<Self::Data>(rules.state).unwrap_or_else(|e| panic!("{e}"));
+        let field = Array2::from_shape_simple_fn((numx, numy), || {
+            if rng.gen_bool(probability) {
+                state
+            } else {
+                Self::Data::default()
+            }
+        });
+        Self::new(field, rules)
+    }
+
+    /// Returns a snapshot of the field and the rule.
+    fn to_board(&self) -> Board<Self::Data>;
+
+    /// Compute the next generation and return whether any cell changed.
+    fn compute_next_generation(&mut self) -> bool;
 
     /// Returns the value at (x,y) and None if index out of bounds.
-    fn cell(&self, x: usize, y: usize) -> Option<u8>;
+    fn cell(&self, x: usize, y: usize) -> Option<Self::Data>;
+    /// Sets the value at (x,y) and returns false if index out of bounds.
+    fn set_cell(&mut self, x: usize, y: usize, value: Self::Data) -> bool;
     /// Returns the number of columns.
     fn numx(&self) -> usize;
     /// Returns the number of rows.
     fn numy(&self) -> usize;
     /// Returns the state.
-    fn state(&self) -> u8;
+    fn state(&self) -> Self::Data;
+
+    /// Returns a copy of the field.
+    fn snapshot(&self) -> Array2<Self::Data> {
+        Array2::from_shape_fn((self.numx(), self.numy()), |(x, y)| {
+            self.cell(x, y).unwrap()
+        })
+    }
+
+    /// Returns the number of living neighbors of every cell, which decides its next state under the rule.
+    /// Custom kernels weight the neighbors, totalistic rules count the cell itself, and counts above 255 saturate.
+    /// By default, cells outside the field are dead as for `BorderRule::Constant`.
+    ///
+    /// ```
+    /// use game_of_life::formats::board_from_str;
+    /// use game_of_life::gameoflife::*;
+    ///
+    /// let blinker = board_from_str("...\n###\n...", 1).unwrap();
+    /// let gol = GameOfLifeStd::new(blinker, Rule::default());
+    /// assert_eq!(gol.neighbor_counts().t(), ndarray::arr2(&[[2, 3, 2], [1, 2, 1], [2, 3, 2]]));
+    /// ```
+    fn neighbor_counts(&self) -> Array2<u8> {
+        let rule = self.to_board().rule;
+        let field = self.snapshot();
+        Array2::from_shape_fn(field.dim(), |(x, y)| {
+            saturate(count_living(&field, x, y, &rule, BorderRule::Constant))
+        })
+    }
+
+    /// Renders the field row by row with living cells as `#`, walls as `X` and all other cells as `.`.
+    ///
+    /// ```
+    /// use game_of_life::gameoflife::*;
+    /// use game_of_life::patterns::Pattern;
+    ///
+    /// let field = Pattern::Blinker.place(5, 5, 1).unwrap();
+    /// let mut gol = GameOfLifeStd::new(field, Rule::default());
+    /// assert_eq!(gol.to_ascii(), ".....\n.....\n.###.\n.....\n.....");
+    ///
+    /// gol.compute_next_generation();
+    /// assert_eq!(gol.to_ascii(), ".....\n..#..\n..#..\n..#..\n.....");
+    /// ```
+    fn to_ascii(&self) -> String {
+        (0..self.numy())
+            .map(|y| {
+                (0..self.numx())
+                    .map(|x| match self.cell(x, y).unwrap() {
+                        cell if cell == Self::Data::WALL => 'X',
+                        cell if cell == self.state() => '#',
+                        _ => '.',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Returns an iterator over the fields of the successive generations, starting with the current one.
+    fn generations(self) -> GenerationIter<Self>
+    where
+        Self: Sized,
+    {
+        GenerationIter { gameoflife: self }
+    }
+}
+
+/// Iterator over the generations of a Game of Life created by [`GameOfLife::generations`].
+pub struct GenerationIter<G: GameOfLife> {
+    gameoflife: G,
+}
+
+impl<G: GameOfLife> Iterator for GenerationIter<G> {
+    type Item = Array2<G::Data>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let field = self.gameoflife.snapshot();
+        self.gameoflife.compute_next_generation();
+        Some(field)
+    }
 }
 
 /// Computes the time steps using ordinary iterations.
-pub struct GameOfLifeStd {
-    field: Array2<AtomicU8>,
+///
+/// The next generation is written into a second buffer while the current one is only read, after which both are swapped.
+/// The cells are updated in parallel on the current `rayon` thread pool, so running inside `ThreadPool::install` limits the number of threads.
+/// The cells are `u8` by default, see [`Cell`] for rules with more states.
+#[derive(Clone)]
+pub struct GameOfLifeStd<T: Cell = u8> {
+    field: Array2<T>,
+    buffer: Array2<T>,
     rules: Rule,
+    /// The state of the rule as a cell
+    state: T,
     numx: usize,
     numy: usize,
 }
 
-impl GameOfLifeStd {
-    /// Counts the living neighbors of the cell at (x, y).
+impl<T: Cell> Default for GameOfLifeStd<T> {
+    /// An empty 10x10 field with the default rule.
+    fn default() -> Self {
+        Self::new(Array2::from_elem((10, 10), T::default()), Rule::default())
+    }
+}
+
+/// Counts the living neighbors of the cell at (x, y), weighted for custom kernels.
+/// A living cell counts itself as well if the rule includes the center.
+fn count_living<T: Cell>(
+    field: &Array2<T>,
+    x: usize,
+    y: usize,
+    rule: &Rule,
+    border: BorderRule,
+) -> usize {
+    let (numx, numy) = field.dim();
+    let alive = |cell: T| cell.into() == rule.state;
+    let center = rule.include_center && alive(field[[x, y]]);
+    center as usize
+        + weighted_neighbors(x, y, numx, numy, &rule.neighbor, border)
+            .filter(|&((nx, ny), _)| alive(field[[nx, ny]]))
+            .map(|(_, weight)| weight as usize)
+            .sum::<usize>()
+}
+
+/// Converts a count into the `u8` of [`GameOfLife::neighbor_counts`], saturating at 255.
+fn saturate(count: usize) -> u8 {
+    u8::try_from(count).unwrap_or(u8::MAX)
+}
+
+impl<T: Cell> GameOfLifeStd<T> {
+    /// Counts the living neighbors of the cell at (x, y), see [`count_living`].
     fn count_living_neighbors(&self, x: usize, y: usize) -> usize {
-        match self.rules.neighbor {
-            NeighborRule::Moore => {
-                let right_border = if x + 1 < self.numx { x + 2 } else { self.numx };
-                let left_border = if x > 1 { x - 1 } else { 0 };
-                let bottom_border = if y + 1 < self.numy { y + 2 } else { self.numy };
-                let top_border = if y > 1 { y - 1 } else { 0 };
-                self.field
-                    .slice(s![left_border..right_border, top_border..bottom_border])
-                    .map(|x| (x.load(Ordering::Relaxed) == self.rules.state) as usize)
-                    .sum()
-                    - (self.field[[x, y]].load(Ordering::Relaxed) == self.rules.state) as usize
-            }
-            NeighborRule::VonNeumann => {
-                let mut sum = 0;
-                if x != 0 {
-                    if let Some(cell) = self.cell(x - 1, y) {
-                        sum += (cell == self.rules.state) as usize;
-                    }
-                }
-                if let Some(cell) = self.cell(x + 1, y) {
-                    sum += (cell == self.rules.state) as usize;
-                }
-                if y != 0 {
-                    if let Some(cell) = self.cell(x, y - 1) {
-                        sum += (cell == self.rules.state) as usize;
-                    }
-                }
-                if let Some(cell) = self.cell(x, y + 1) {
-                    sum += (cell == self.rules.state) as usize;
-                }
-                sum
-            }
-        }
+        count_living(&self.field, x, y, &self.rules, BorderRule::Constant)
     }
 }
 
-impl GameOfLife for GameOfLifeStd {
-    type Data = AtomicU8;
+impl<T: Cell> GameOfLife for GameOfLifeStd<T> {
+    type Data = T;
 
-    fn new(field: Array2<AtomicU8>, rules: Rule) -> Self {
+    fn new(field: Array2<T>, rules: Rule) -> Self {
         let shape = field.shape().to_owned();
         let numx = shape[0];
         let numy = shape[1];
+        if let Err(e) = check_dimensions(numx, numy, usize::MAX) {
+            panic!("{e}");
+        }
+        let state = check_state(rules.state).unwrap_or_else(|e| panic!("{e}"));
+        if let Err(e) = check_neighbor(&rules.neighbor) {
+            panic!("{e}");
+        }
+        let buffer = Array2::from_elem(field.raw_dim(), T::default());
         Self {
             field,
+            buffer,
             rules,
+            state,
             numx,
             numy,
         }
     }
 
-    fn compute_next_generation(&mut self) {
-        let mut temp = Array2::<AtomicU8>::default((self.numx, self.numy));
+    fn compute_next_generation(&mut self) -> bool {
+        // Taking the buffer out of `self` allows reading the current field while writing the next one.
+        // If a previous generation panicked, the buffer was never put back and is allocated again,
+        // so that the panic leaves the field as it was instead of breaking every later generation.
+        let mut next = std::mem::take(&mut self.buffer);
+        if next.dim() != self.field.dim() {
+            next = Array2::from_elem(self.field.raw_dim(), T::default());
+        }
+        let changed = AtomicBool::new(false);
         Zip::indexed(&self.field)
-            .and(&mut temp)
-            .par_for_each(|(x, y), elem_field, elem_temp| {
+            .and(&mut next)
+            .par_for_each(|(x, y), &elem_field, elem_next| {
                 let count = self.count_living_neighbors(x, y);
-                let elem_field = elem_field.load(Ordering::Relaxed);
 
-                if self.rules.birth[count]
-                    || (elem_field == self.rules.state && self.rules.survival[count])
+                *elem_next = if elem_field == T::WALL {
+                    T::WALL
+                } else if (elem_field == T::default() && self.rules.is_born(count))
+                    || (elem_field == self.state && self.rules.survives(count))
                 {
-                    elem_temp.store(self.rules.state, Ordering::Relaxed);
-                } else if elem_field != 0 {
-                    elem_temp.store(elem_field - 1, Ordering::Relaxed);
+                    self.state
+                } else {
+                    elem_field.decay()
+                };
+                if *elem_next != elem_field {
+                    changed.store(true, Ordering::Relaxed);
                 }
             });
-        self.field = temp;
+        self.buffer = std::mem::replace(&mut self.field, next);
+        changed.into_inner()
     }
 
-    fn cell(&self, x: usize, y: usize) -> Option<u8> {
-        if let Some(cell) = self.field.get((x, y)) {
-            return Some(cell.load(Ordering::Relaxed));
+    fn cell(&self, x: usize, y: usize) -> Option<T> {
+        self.field.get((x, y)).copied()
+    }
+
+    fn set_cell(&mut self, x: usize, y: usize, value: T) -> bool {
+        if let Some(cell) = self.field.get_mut((x, y)) {
+            *cell = value;
+            return true;
+        }
+        false
+    }
+
+    fn to_board(&self) -> Board<T> {
+        Board {
+            numx: self.numx,
+            numy: self.numy,
+            cells: self.field.iter().copied().collect(),
+            rule: self.rules.clone(),
         }
-        None
     }
 
     fn numx(&self) -> usize {
@@ -222,62 +1168,167 @@ impl GameOfLife for GameOfLifeStd {
         self.numy
     }
 
-    fn state(&self) -> u8 {
-        self.rules.state
+    fn state(&self) -> T {
+        self.state
+    }
+
+    fn snapshot(&self) -> Array2<T> {
+        self.field.clone()
+    }
+
+    fn neighbor_counts(&self) -> Array2<u8> {
+        Array2::from_shape_fn(self.field.dim(), |(x, y)| {
+            saturate(self.count_living_neighbors(x, y))
+        })
     }
 }
 
 /// Computes the time steps using `ndarray_ndimage`'s `convolve`.
-pub struct GameOfLifeConvolution {
-    field: Array2<u8>,
+/// The cells are `u8` by default, see [`Cell`] for rules with more states.
+#[derive(Clone)]
+pub struct GameOfLifeConvolution<T: Cell = u8> {
+    field: Array2<T>,
     rules: Rule,
+    /// The state of the rule as a cell
+    state: T,
+    border: BorderRule,
+    /// Kernels of the neighbor rule, flipped in advance as the convolution flips them again.
+    /// The hexagonal rule has a second kernel for the odd rows, and the center is 1 if the rule includes it.
+    kernels: Vec<Array2<usize>>,
+    /// Buffer for the living cells, which are convolved with the kernels
+    alive: Array2<usize>,
     numx: usize,
     numy: usize,
 }
 
-impl GameOfLife for GameOfLifeConvolution {
-    type Data = u8;
+impl<T: Cell> Default for GameOfLifeConvolution<T> {
+    /// An empty 10x10 field with the default rule.
+    fn default() -> Self {
+        Self::new(Array2::from_elem((10, 10), T::default()), Rule::default())
+    }
+}
+
+impl<T: Cell> GameOfLifeConvolution<T> {
+    /// Sets how the field is extended beyond its borders, which is `BorderRule::Constant` by default.
+    pub fn with_border(mut self, border: BorderRule) -> Self {
+        self.border = border;
+        self
+    }
 
-    fn new(field: Array2<u8>, rules: Rule) -> Self {
+    /// Convolves the living cells with the kernels, taking the count of every cell from the kernel of its row.
+    ///
+    /// `convolve` has no output parameter, so the counts are allocated anew every generation.
+    /// Only the living cells are kept in a buffer on the struct.
+    fn count(&self, alive: &Array2<usize>) -> Array2<usize> {
+        let border = self.border.into_border_mode();
+        let mut counts = convolve(alive, &self.kernels[0], border, 0);
+        // Odd rows are shifted to the right for the hexagonal rule, so they have their own kernel.
+        if let Some(odd) = self.kernels.get(1) {
+            let odd = convolve(alive, odd, border, 0);
+            Zip::indexed(&mut counts)
+                .and(&odd)
+                .for_each(|(_, y), count, &odd| {
+                    if y % 2 == 1 {
+                        *count = odd;
+                    }
+                });
+        }
+        counts
+    }
+}
+
+impl<T: Cell> GameOfLife for GameOfLifeConvolution<T> {
+    type Data = T;
+
+    fn new(field: Array2<T>, rules: Rule) -> Self {
         let shape = field.shape().to_owned();
         let numx = shape[0];
         let numy = shape[1];
+        if let Err(e) = check_dimensions(numx, numy, usize::MAX) {
+            panic!("{e}");
+        }
+        let state = check_state(rules.state).unwrap_or_else(|e| panic!("{e}"));
+        if let Err(e) = check_neighbor(&rules.neighbor) {
+            panic!("{e}");
+        }
+        let rows = if rules.neighbor == NeighborRule::Hex {
+            2
+        } else {
+            1
+        };
+        let kernels = (0..rows)
+            .map(|y| {
+                let kernel = rules.neighbor.kernel(y);
+                let size = kernel.nrows();
+                let mut kernel = Array2::from_shape_fn((size, size), |(x, y)| {
+                    kernel[[size - 1 - x, size - 1 - y]] as usize
+                });
+                if rules.include_center {
+                    kernel[[size / 2, size / 2]] = 1;
+                }
+                kernel
+            })
+            .collect();
         Self {
+            alive: Array2::zeros((numx, numy)),
             field,
             rules,
+            state,
+            border: BorderRule::default(),
+            kernels,
             numx,
             numy,
         }
     }
 
-    fn compute_next_generation(&mut self) {
-        let kernel = match self.rules.neighbor {
-            NeighborRule::Moore => arr2(&[[1, 1, 1], [1, 0, 1], [1, 1, 1]]),
-            NeighborRule::VonNeumann => arr2(&[[0, 1, 0], [1, 0, 1], [0, 1, 0]]),
+    fn compute_next_generation(&mut self) -> bool {
+        let state = self.state;
+        Zip::from(&mut self.alive)
+            .and(&self.field)
+            .for_each(|alive, &cell| *alive = (cell == state) as usize);
+        let counts = self.count(&self.alive);
+        // Living cells that survive or dead cells that are born are set to `state`, all other cells decay.
+        let rules = &self.rules;
+        let mut changed = false;
+        let mut update = |cell: &mut T, count: usize| {
+            if *cell == T::WALL {
+                return;
+            }
+            let next = if (*cell == T::default() && rules.is_born(count))
+                || (*cell == state && rules.survives(count))
+            {
+                state
+            } else {
+                cell.decay()
+            };
+            changed |= next != *cell;
+            *cell = next;
         };
+        Zip::from(&mut self.field)
+            .and(&counts)
+            .for_each(|cell, &count| update(cell, count));
+        changed
+    }
 
-        let temp = convolve(
-            &self.field.map(|elem| (*elem == self.rules.state) as usize),
-            &kernel,
-            ndarray_ndimage::BorderMode::Constant(0),
-            0,
-        );
+    fn cell(&self, x: usize, y: usize) -> Option<T> {
+        self.field.get((x, y)).copied()
+    }
 
-        let survive = temp.map(|elem| self.rules.survival[*elem] as u8);
-        let birth = temp.map(|elem| self.rules.birth[*elem] as u8);
-        self.field = self.field.map(|elem| (*elem == self.rules.state) as u8) * &survive
-            + (self.field.map(|elem| *elem) * survive.map(|elem| 1 - elem)).map(|elem| {
-                if *elem != 0 {
-                    *elem - 1
-                } else {
-                    0
-                }
-            });
-        self.field += &(self.field.map(|elem| self.rules.state - elem) * birth);
+    fn set_cell(&mut self, x: usize, y: usize, value: T) -> bool {
+        if let Some(cell) = self.field.get_mut((x, y)) {
+            *cell = value;
+            return true;
+        }
+        false
     }
 
-    fn cell(&self, x: usize, y: usize) -> Option<u8> {
-        self.field.get((x, y)).copied()
+    fn to_board(&self) -> Board<T> {
+        Board {
+            numx: self.numx,
+            numy: self.numy,
+            cells: self.field.iter().copied().collect(),
+            rule: self.rules.clone(),
+        }
     }
 
     fn numx(&self) -> usize {
@@ -288,71 +1339,207 @@ impl GameOfLife for GameOfLifeConvolution {
         self.numy
     }
 
-    fn state(&self) -> u8 {
-        self.rules.state
+    fn state(&self) -> T {
+        self.state
+    }
+
+    fn snapshot(&self) -> Array2<T> {
+        self.field.clone()
+    }
+
+    /// Counts with the border rule of the convolution, see [`GameOfLifeConvolution::with_border`].
+    fn neighbor_counts(&self) -> Array2<u8> {
+        let alive = self.field.mapv(|cell| (cell == self.state) as usize);
+        self.count(&alive).mapv(saturate)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use ndarray::Array1;
-    use rand::Rng;
+    use ndarray::{arr2, s};
+    use rand::{rngs::StdRng, SeedableRng};
 
     use super::*;
+    use crate::builder::*;
+    use crate::formats::board_from_str;
 
     #[test]
     fn count_living_neighbors_moore() {
-        let arr = arr2(&[[1, 1, 1], [1, 1, 1], [1, 1, 1]]).map(|elem| AtomicU8::new(*elem));
+        let arr = arr2(&[[1, 1, 1], [1, 1, 1], [1, 1, 1]]);
         let rules = Rule::new(
             LifeRule::Raw([false, false, true, true, false, false, false, false, false]),
             LifeRule::Raw([false, false, false, true, false, false, false, false, false]),
             1,
             NeighborRule::Moore,
+        )
+        .unwrap();
+        let gol_std = GameOfLifeStd::<u8>::new(arr.clone(), rules.clone());
+        let gol_conv = GameOfLifeConvolution::<u8>::new(arr, rules);
+
+        let counts = arr2(&[[3, 5, 3], [5, 8, 5], [3, 5, 3]]);
+        assert_eq!(gol_std.neighbor_counts(), counts);
+        assert_eq!(gol_conv.neighbor_counts(), counts);
+        assert_eq!(
+            gol_conv.with_border(BorderRule::Wrap).neighbor_counts(),
+            Array2::from_elem((3, 3), 8)
         );
-        let gol = GameOfLifeStd::new(arr, rules);
+    }
+
+    #[test]
+    fn count_living_neighbors_von_neumann() {
+        let arr = arr2(&[[1, 1, 1], [1, 1, 1], [1, 1, 1]]);
+        let rules = Rule::new(
+            LifeRule::Raw([false, false, true, true, false, false, false, false, false]),
+            LifeRule::Raw([false, false, false, true, false, false, false, false, false]),
+            1,
+            NeighborRule::VonNeumann,
+        )
+        .unwrap();
+        let gol = GameOfLifeStd::<u8>::new(arr, rules);
 
         let mut temp = Array2::zeros((3, 3));
         for ((x, y), _) in gol.field.indexed_iter() {
             temp[[x, y]] = gol.count_living_neighbors(x, y);
         }
 
-        assert_eq!(temp, arr2(&[[3, 5, 3], [5, 8, 5], [3, 5, 3]]));
+        assert_eq!(temp, arr2(&[[2, 3, 2], [3, 4, 3], [2, 3, 2]]));
     }
 
     #[test]
-    fn count_living_neighbors_von_neumann() {
-        let arr = arr2(&[[1, 1, 1], [1, 1, 1], [1, 1, 1]]).map(|elem| AtomicU8::new(*elem));
+    fn count_living_neighbors_hex() {
+        let arr = Array2::ones((4, 4));
         let rules = Rule::new(
             LifeRule::Raw([false, false, true, true, false, false, false, false, false]),
             LifeRule::Raw([false, false, false, true, false, false, false, false, false]),
             1,
-            NeighborRule::VonNeumann,
-        );
-        let gol = GameOfLifeStd::new(arr, rules);
+            NeighborRule::Hex,
+        )
+        .unwrap();
+        let mut gol = GameOfLifeStd::<u8>::new(arr, rules);
 
-        let mut temp = Array2::zeros((3, 3));
+        let mut temp = Array2::zeros((4, 4));
         for ((x, y), _) in gol.field.indexed_iter() {
             temp[[x, y]] = gol.count_living_neighbors(x, y);
         }
+        // Even rows lack two neighbors on the left edge, odd rows on the right edge.
+        assert_eq!(
+            temp.t(),
+            arr2(&[[2, 4, 4, 3], [5, 6, 6, 3], [3, 6, 6, 5], [3, 4, 4, 2]])
+        );
 
-        assert_eq!(temp, arr2(&[[2, 3, 2], [3, 4, 3], [2, 3, 2]]));
+        // A single living cell in an odd and in an even row is a neighbor of exactly its six neighbors.
+        for ((x, y), neighbors) in [
+            ((1, 1), [(0, 1), (2, 1), (1, 0), (2, 0), (1, 2), (2, 2)]),
+            ((2, 2), [(1, 2), (3, 2), (1, 1), (2, 1), (1, 3), (2, 3)]),
+        ] {
+            gol.field.fill(0);
+            gol.field[[x, y]] = 1;
+            for ((nx, ny), _) in gol.field.indexed_iter() {
+                assert_eq!(
+                    gol.count_living_neighbors(nx, ny),
+                    neighbors.contains(&(nx, ny)) as usize,
+                    "({nx}, {ny}) next to ({x}, {y})"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn neighbor_coords_edges() {
+        use BorderRule::*;
+        use NeighborRule::*;
+        let coords = |x, y, neighbor, border| {
+            let mut coords: Vec<_> = neighbor_coords(x, y, 4, 3, &neighbor, border).collect();
+            coords.sort();
+            coords
+        };
+
+        assert_eq!(coords(0, 0, Moore, Constant), [(0, 1), (1, 0), (1, 1)]);
+        assert_eq!(
+            coords(3, 1, Moore, Constant),
+            [(2, 0), (2, 1), (2, 2), (3, 0), (3, 2)]
+        );
+        assert_eq!(coords(1, 2, VonNeumann, Constant), [(0, 2), (1, 1), (2, 2)]);
+        assert_eq!(
+            coords(0, 0, Moore, Wrap),
+            [
+                (0, 1),
+                (0, 2),
+                (1, 0),
+                (1, 1),
+                (1, 2),
+                (3, 0),
+                (3, 1),
+                (3, 2)
+            ]
+        );
+        assert_eq!(
+            coords(3, 2, VonNeumann, Wrap),
+            [(0, 2), (2, 2), (3, 0), (3, 1)]
+        );
+        // Even rows have their diagonal neighbors on the left, odd rows on the right.
+        assert_eq!(coords(0, 0, Hex, Constant), [(0, 1), (1, 0)]);
+        assert_eq!(coords(3, 1, Hex, Constant), [(2, 1), (3, 0), (3, 2)]);
+        assert_eq!(
+            coords(0, 1, Hex, Wrap),
+            [(0, 0), (0, 2), (1, 0), (1, 1), (1, 2), (3, 1)]
+        );
+
+        // The other border rules map the outside cells into the field.
+        assert_eq!(
+            coords(0, 0, VonNeumann, Nearest),
+            [(0, 0), (0, 0), (0, 1), (1, 0)]
+        );
+        assert_eq!(
+            coords(0, 0, VonNeumann, Reflect),
+            [(0, 0), (0, 0), (0, 1), (1, 0)]
+        );
+        assert_eq!(
+            coords(0, 0, VonNeumann, Mirror),
+            [(0, 1), (0, 1), (1, 0), (1, 0)]
+        );
+
+        let kernel = NeighborRule::custom(Array2::from_shape_fn((5, 5), |(x, y)| {
+            (x == 4 && y == 2) as i32
+        }))
+        .unwrap();
+        assert_eq!(coords(2, 1, kernel.clone(), Constant), []);
+        assert_eq!(coords(1, 1, kernel, Constant), [(3, 1)]);
+    }
+
+    #[test]
+    fn panicking_generation_std() {
+        let blinker = Array2::from_shape_fn((5, 5), |(x, y)| (x == 2 && (1..4).contains(&y)) as u8);
+        let mut gol = GameOfLifeStd::new(blinker.clone(), Rule::default());
+
+        // A generation that panics inside the parallel loop has taken the buffer out of `self` and never puts it back.
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _next = std::mem::take(&mut gol.buffer);
+            panic!("injected failure");
+        }));
+        assert!(result.is_err());
+        assert_eq!(gol.field, blinker);
+        assert_eq!(gol.buffer.dim(), (0, 0));
+
+        assert!(gol.compute_next_generation());
+        assert!(gol.compute_next_generation());
+        assert_eq!(gol.field, blinker);
     }
 
     #[test]
     fn compute_next_generation_std() {
-        let arr = arr2(&[[1, 1, 1], [1, 1, 1], [1, 1, 1]]).map(|elem| AtomicU8::new(*elem));
+        let arr = arr2(&[[1, 1, 1], [1, 1, 1], [1, 1, 1]]);
         let rules = Rule::new(
             LifeRule::Raw([false, false, true, true, false, false, false, false, false]),
             LifeRule::Raw([false, false, false, true, false, false, false, false, false]),
             1,
             NeighborRule::Moore,
-        );
-        let mut gol = GameOfLifeStd::new(arr, rules);
+        )
+        .unwrap();
+        let mut gol = GameOfLifeStd::<u8>::new(arr, rules);
 
         gol.compute_next_generation();
-        let temp = gol.field.map(|elem| elem.load(Ordering::Relaxed));
-
-        assert_eq!(temp, arr2(&[[1, 0, 1], [0, 0, 0], [1, 0, 1]]));
+        assert_eq!(gol.field, arr2(&[[1, 0, 1], [0, 0, 0], [1, 0, 1]]));
     }
 
     #[test]
@@ -369,7 +1556,8 @@ mod test {
             LifeRule::Raw([false, false, false, true, false, false, false, false, false]),
             state,
             NeighborRule::Moore,
-        );
+        )
+        .unwrap();
         let mut gol = GameOfLifeConvolution::new(arr, rules);
 
         gol.compute_next_generation();
@@ -386,35 +1574,59 @@ mod test {
 
     #[test]
     fn algorithms_moore() {
-        let mut rng = rand::thread_rng();
+        let builder = GameBuilder::new()
+            .dimensions(10, 10)
+            .rule(
+                Rule::new(
+                    LifeRule::Raw([false, false, true, true, false, false, false, false, false]),
+                    LifeRule::Raw([false, false, false, true, false, false, false, false, false]),
+                    2,
+                    NeighborRule::VonNeumann,
+                )
+                .unwrap(),
+            )
+            .random(0.3, Some(rand::random()));
+
+        let mut gol_std = builder.clone().algorithm(Algorithm::Std).build().unwrap();
+        let mut gol_conv = builder.algorithm(Algorithm::Conv).build().unwrap();
 
-        let numx: usize = 10;
-        let numy: usize = 10;
-        let field_vec_std: Vec<u8> = (0..numx * numy).map(|_| rng.gen_bool(0.3) as u8).collect();
-        let field_vec_conv = field_vec_std.clone();
+        assert_eq!(
+            gol_std.snapshot(),
+            gol_conv.snapshot(),
+            "standard and convolution differ"
+        );
 
-        let field_std = Array1::<u8>::from_vec(field_vec_std)
-            .map(|elem| AtomicU8::new(*elem))
-            .into_shape((numx, numy))
-            .unwrap();
-        let field_conv = Array1::<u8>::from_vec(field_vec_conv)
-            .into_shape((numx, numy))
-            .unwrap();
+        gol_std.compute_next_generation();
+        gol_conv.compute_next_generation();
 
-        let rules_std = Rule::new(
-            LifeRule::Raw([false, false, true, true, false, false, false, false, false]),
-            LifeRule::Raw([false, false, false, true, false, false, false, false, false]),
-            2,
-            NeighborRule::VonNeumann,
+        assert_eq!(
+            gol_std.snapshot(),
+            gol_conv.snapshot(),
+            "standard and convolution differ after one iteration"
         );
-        let rules_conv = rules_std.clone();
+    }
 
-        let mut gol_std = GameOfLifeStd::new(field_std, rules_std);
-        let mut gol_conv = GameOfLifeConvolution::new(field_conv, rules_conv);
+    #[test]
+    fn algorithms_von_neumann() {
+        let builder = GameBuilder::new()
+            .dimensions(10, 10)
+            .rule(
+                Rule::new(
+                    LifeRule::Raw([false, false, true, true, false, false, false, false, false]),
+                    LifeRule::Raw([false, false, false, true, false, false, false, false, false]),
+                    1,
+                    NeighborRule::VonNeumann,
+                )
+                .unwrap(),
+            )
+            .random(0.3, Some(rand::random()));
+
+        let mut gol_std = builder.clone().algorithm(Algorithm::Std).build().unwrap();
+        let mut gol_conv = builder.algorithm(Algorithm::Conv).build().unwrap();
 
         assert_eq!(
-            gol_std.field.map(|elem| elem.load(Ordering::Relaxed)),
-            gol_conv.field,
+            gol_std.snapshot(),
+            gol_conv.snapshot(),
             "standard and convolution differ"
         );
 
@@ -422,53 +1634,731 @@ mod test {
         gol_conv.compute_next_generation();
 
         assert_eq!(
-            gol_std.field.map(|elem| elem.load(Ordering::Relaxed)),
-            gol_conv.field,
+            gol_std.snapshot(),
+            gol_conv.snapshot(),
             "standard and convolution differ after one iteration"
         );
     }
 
     #[test]
-    fn algorithms_von_neumann() {
-        let mut rng = rand::thread_rng();
-
-        let numx: usize = 10;
-        let numy: usize = 10;
-        let field_vec_std: Vec<u8> = (0..numx * numy).map(|_| rng.gen_bool(0.3) as u8).collect();
-        let field_vec_conv = field_vec_std.clone();
+    fn algorithms_hex() {
+        let builder = GameBuilder::new()
+            .dimensions(11, 10)
+            .rule(
+                Rule::new(
+                    LifeRule::Numbers(&[3, 4]),
+                    LifeRule::Numbers(&[2]),
+                    1,
+                    NeighborRule::Hex,
+                )
+                .unwrap(),
+            )
+            .random(0.3, Some(rand::random()));
+
+        let mut gol_std = builder.clone().algorithm(Algorithm::Std).build().unwrap();
+        let mut gol_conv = builder.algorithm(Algorithm::Conv).build().unwrap();
+
+        for generation in 0..10 {
+            assert_eq!(
+                gol_std.snapshot(),
+                gol_conv.snapshot(),
+                "standard and convolution differ after {generation} iterations"
+            );
+            gol_std.compute_next_generation();
+            gol_conv.compute_next_generation();
+        }
+    }
 
-        let field_std = Array1::<u8>::from_vec(field_vec_std)
-            .map(|elem| AtomicU8::new(*elem))
-            .into_shape((numx, numy))
+    #[test]
+    fn algorithms_custom_kernel() {
+        // A 5x5 kernel that counts the inner ring twice and the outer ring once, as well as a lopsided one.
+        let ring = Array2::from_shape_fn((5, 5), |(x, y)| {
+            match (x as i32 - 2).abs().max((y as i32 - 2).abs()) {
+                0 => 0,
+                1 => 2,
+                _ => 1,
+            }
+        });
+        let lopsided = Array2::from_shape_fn((5, 5), |(x, y)| (x > 2 && y != 2) as i32);
+        for (kernel, survival, birth) in [(ring, 6..12, 8..11), (lopsided, 4..7, 6..9)] {
+            let neighbor = NeighborRule::custom(kernel).unwrap();
+            let rules = Rule::new(
+                LifeRule::Range(survival),
+                LifeRule::Range(birth),
+                2,
+                neighbor,
+            )
             .unwrap();
-        let field_conv = Array1::<u8>::from_vec(field_vec_conv)
-            .into_shape((numx, numy))
+            assert_eq!(rules.survival.len(), rules.neighbor.max_neighbors() + 1);
+
+            let builder = GameBuilder::new()
+                .dimensions(12, 9)
+                .rule(rules)
+                .random(0.4, Some(rand::random()));
+            let mut gol_std = builder.clone().algorithm(Algorithm::Std).build().unwrap();
+            let mut gol_conv = builder.algorithm(Algorithm::Conv).build().unwrap();
+
+            for generation in 0..10 {
+                assert_eq!(
+                    gol_std.snapshot(),
+                    gol_conv.snapshot(),
+                    "standard and convolution differ after {generation} iterations"
+                );
+                gol_std.compute_next_generation();
+                gol_conv.compute_next_generation();
+            }
+        }
+
+        assert_eq!(
+            NeighborRule::custom(Array2::ones((3, 3))),
+            Err(KernelError::NonZeroCenter)
+        );
+        assert_eq!(
+            NeighborRule::custom(Array2::zeros((4, 4))),
+            Err(KernelError::Shape(4, 4))
+        );
+        assert_eq!(
+            NeighborRule::custom(Array2::zeros((3, 5))),
+            Err(KernelError::Shape(3, 5))
+        );
+        assert_eq!(
+            NeighborRule::custom(arr2(&[[0, 0, 0], [0, 0, -1], [0, 0, 0]])),
+            Err(KernelError::NegativeWeight)
+        );
+        assert_eq!(
+            NeighborRule::custom(arr2(&[[i32::MAX, 0, 0], [0, 0, 0], [0, 0, i32::MAX]])),
+            Err(KernelError::TooManyNeighbors(2 * i32::MAX as u64))
+        );
+    }
+
+    #[test]
+    fn algorithms_include_center() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for neighbor in [
+            NeighborRule::Moore,
+            NeighborRule::VonNeumann,
+            NeighborRule::Hex,
+            NeighborRule::VonNeumannR(2),
+        ] {
+            let rules = Rule::totalistic(LifeRule::Range(3..6), LifeRule::Range(2..4), 3, neighbor)
+                .unwrap();
+            assert_eq!(rules.survival.len(), rules.max_count().max(8) + 1);
+            let field = GameOfLifeStd::random(20, 15, rules.clone(), 0.4, &mut rng).snapshot();
+            let mut gol_std = GameOfLifeStd::<u8>::new(field.clone(), rules.clone());
+            let mut gol_conv = GameOfLifeConvolution::new(field, rules);
+
+            for generation in 0..10 {
+                assert_eq!(
+                    gol_std.snapshot(),
+                    gol_conv.snapshot(),
+                    "standard and convolution differ after {generation} iterations"
+                );
+                gol_std.compute_next_generation();
+                gol_conv.compute_next_generation();
+            }
+        }
+
+        // Counting the cell itself shifts the survival counts by one.
+        let life = GameOfLifeStd::<u8>::random(20, 15, Rule::default(), 0.4, &mut rng);
+        let totalistic = Rule::totalistic(
+            LifeRule::Range(3..5),
+            LifeRule::One(3),
+            1,
+            NeighborRule::Moore,
+        )
+        .unwrap();
+        let gol_std = GameOfLifeStd::new(life.snapshot(), totalistic.clone());
+        let gol_conv = GameOfLifeConvolution::new(life.snapshot(), totalistic);
+        let expected: Vec<_> = life.generations().take(10).collect();
+        assert_eq!(gol_std.generations().take(10).collect::<Vec<_>>(), expected);
+        assert_eq!(
+            gol_conv.generations().take(10).collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn algorithms_von_neumann_radius() {
+        assert_eq!(
+            NeighborRule::VonNeumannR(1).kernel(0),
+            NeighborRule::VonNeumann.kernel(0)
+        );
+        assert_eq!(
+            NeighborRule::from_str("VN3"),
+            Ok(NeighborRule::VonNeumannR(3))
+        );
+        assert_eq!(NeighborRule::from_str("vnx"), Err(()));
+
+        // Radii beyond `MAX_RADIUS` are rejected instead of allocating huge rule arrays or overflowing.
+        assert_eq!(
+            NeighborRule::from_str("vn2047"),
+            Ok(NeighborRule::VonNeumannR(MAX_RADIUS))
+        );
+        for radius in ["vn2048", "vn1000000", "vn18446744073709551615"] {
+            assert_eq!(NeighborRule::from_str(radius), Err(()), "{radius}");
+        }
+        assert_eq!(
+            NeighborRule::VonNeumannR(usize::MAX).max_neighbors(),
+            usize::MAX
+        );
+        assert_eq!(
+            Rule::new(
+                LifeRule::One(2),
+                LifeRule::One(3),
+                1,
+                NeighborRule::VonNeumannR(1_000_000)
+            ),
+            Err(GolError::InvalidRadius {
+                radius: 1_000_000,
+                max_radius: MAX_RADIUS
+            })
+        );
+
+        for (radius, survival, birth) in [(2, 3..7, 4..6), (3, 6..13, 8..12)] {
+            let neighbor = NeighborRule::VonNeumannR(radius);
+            assert_eq!(neighbor.kernel(0).sum() as usize, neighbor.max_neighbors());
+            let rules = Rule::new(
+                LifeRule::Range(survival),
+                LifeRule::Range(birth),
+                2,
+                neighbor,
+            )
             .unwrap();
 
-        let rules_std = Rule::new(
+            let builder = GameBuilder::new()
+                .dimensions(15, 11)
+                .rule(rules)
+                .random(0.4, Some(rand::random()));
+            let mut gol_std = builder.clone().algorithm(Algorithm::Std).build().unwrap();
+            let mut gol_conv = builder.algorithm(Algorithm::Conv).build().unwrap();
+
+            for generation in 0..10 {
+                assert_eq!(
+                    gol_std.snapshot(),
+                    gol_conv.snapshot(),
+                    "standard and convolution differ after {generation} iterations"
+                );
+                gol_std.compute_next_generation();
+                gol_conv.compute_next_generation();
+            }
+        }
+    }
+
+    #[test]
+    fn dimensions() {
+        assert_eq!(check_dimensions(1, 1, 1), Ok(()));
+        assert_eq!(check_dimensions(4096, 4096, MAX_CELLS), Ok(()));
+        for (numx, numy) in [(0, 10), (10, 0), (4097, 4096), (usize::MAX, 2)] {
+            assert_eq!(
+                check_dimensions(numx, numy, MAX_CELLS),
+                Err(GolError::InvalidDimensions {
+                    numx,
+                    numy,
+                    max_cells: MAX_CELLS
+                })
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one cell")]
+    fn empty_field() {
+        GameOfLifeStd::<u8>::new(Array2::zeros((0, 5)), Rule::default());
+    }
+
+    #[test]
+    fn clone_is_independent() {
+        let arr = arr2(&[[0, 1, 0], [0, 1, 0], [0, 1, 0]]);
+        let rules = Rule::default();
+
+        let gol_std = GameOfLifeStd::<u8>::new(arr.clone(), rules.clone());
+        let mut clone_std = gol_std.clone();
+        clone_std.compute_next_generation();
+        assert_eq!(gol_std.field, arr);
+        assert_ne!(clone_std.field, arr);
+
+        let gol_conv = GameOfLifeConvolution::<u8>::new(arr.clone(), rules);
+        let mut clone_conv = gol_conv.clone();
+        clone_conv.compute_next_generation();
+        assert_eq!(gol_conv.field, arr);
+        assert_ne!(clone_conv.field, arr);
+    }
+
+    #[test]
+    fn set_cell_glider() {
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let mut gol_std = GameOfLifeStd::<u8>::new(Array2::zeros((6, 6)), Rule::default());
+        let mut gol_conv = GameOfLifeConvolution::<u8>::new(Array2::zeros((6, 6)), Rule::default());
+        for (x, y) in glider {
+            assert!(gol_std.set_cell(x, y, 1));
+            assert!(gol_conv.set_cell(x, y, 1));
+        }
+        assert!(!gol_std.set_cell(6, 0, 1));
+        assert!(!gol_conv.set_cell(0, 6, 1));
+
+        for _ in 0..4 {
+            gol_std.compute_next_generation();
+            gol_conv.compute_next_generation();
+        }
+
+        let expected = "......\n..#...\n...#..\n.###..\n......\n......";
+        assert_eq!(gol_std.to_ascii(), expected);
+        assert_eq!(gol_conv.to_ascii(), expected);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn board_round_trip() {
+        let mut rng = rand::thread_rng();
+        let field = Array2::from_shape_simple_fn((10, 8), || rng.gen_bool(0.3) as u8 * 3);
+        let rules = Rule::new(
             LifeRule::Raw([false, false, true, true, false, false, false, false, false]),
             LifeRule::Raw([false, false, false, true, false, false, false, false, false]),
+            3,
+            NeighborRule::VonNeumann,
+        )
+        .unwrap();
+        let mut gol = GameOfLifeConvolution::new(field, rules);
+        gol.compute_next_generation();
+        gol.compute_next_generation();
+
+        let json = serde_json::to_string(&gol.to_board()).unwrap();
+        let board: Board = serde_json::from_str(&json).unwrap();
+        let mut restored = GameOfLifeConvolution::from_board(board).unwrap();
+
+        assert_eq!(restored.field, gol.field);
+        assert_eq!(restored.rules, gol.rules);
+
+        gol.compute_next_generation();
+        restored.compute_next_generation();
+        assert_eq!(restored.field, gol.field);
+    }
+
+    #[test]
+    fn walls_block_glider() {
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let mut field = Array2::zeros((10, 10));
+        for (x, y) in glider {
+            field[[x, y]] = 1;
+        }
+        for x in 0..10 {
+            field[[x, 6]] = WALL;
+        }
+
+        let mut gol_std = GameOfLifeStd::new(field.clone(), Rule::default());
+        let mut gol_conv = GameOfLifeConvolution::new(field, Rule::default());
+        for _ in 0..40 {
+            gol_std.compute_next_generation();
+            gol_conv.compute_next_generation();
+            assert_eq!(gol_std.field, gol_conv.field);
+        }
+
+        // The glider settles into a block in front of the wall.
+        assert_eq!(
+            gol_std.to_ascii(),
+            [
+                "..........",
+                "..........",
+                "..........",
+                "..........",
+                "....##....",
+                "....##....",
+                "XXXXXXXXXX",
+                "..........",
+                "..........",
+                "..........",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn still_life_unchanged() {
+        let mut block = Array2::zeros((4, 4));
+        block.slice_mut(s![1..3, 1..3]).fill(1);
+        block[[3, 3]] = WALL;
+        let mut gol_std = GameOfLifeStd::new(block.clone(), Rule::default());
+        let mut gol_conv = GameOfLifeConvolution::new(block, Rule::default());
+        assert!(!gol_std.compute_next_generation());
+        assert!(!gol_conv.compute_next_generation());
+
+        let blinker = arr2(&[[0, 1, 0], [0, 1, 0], [0, 1, 0]]);
+        let mut gol_std = GameOfLifeStd::<u8>::new(blinker.clone(), Rule::default());
+        let mut gol_conv = GameOfLifeConvolution::<u8>::new(blinker, Rule::default());
+        assert!(gol_std.compute_next_generation());
+        assert!(gol_conv.compute_next_generation());
+    }
+
+    #[test]
+    fn generations_glider() {
+        let field = board_from_str(".#....\n..#...\n###...\n......\n......\n......", 1).unwrap();
+        let translated =
+            board_from_str("......\n..#...\n...#..\n.###..\n......\n......", 1).unwrap();
+
+        let gol = GameOfLifeConvolution::new(field.clone(), Rule::default());
+        let generations: Vec<_> = gol.generations().take(5).collect();
+
+        assert_eq!(generations[0], field);
+        assert_eq!(generations[4], translated);
+    }
+
+    #[test]
+    fn border_wrap_glider() {
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let mut field = Array2::zeros((6, 6));
+        for (x, y) in glider {
+            field[[x, y]] = 1;
+        }
+
+        let gol = GameOfLifeConvolution::<u8>::new(field.clone(), Rule::default())
+            .with_border(BorderRule::Wrap);
+
+        // The glider moves one cell diagonally every four generations.
+        assert_eq!(gol.generations().nth(24).unwrap(), field);
+    }
+
+    #[test]
+    fn life_rule_arrays() {
+        let array = |life_rule: LifeRule| life_rule.try_into_array(4);
+        let error = |count| {
+            Err(GolError::InvalidNeighborCount {
+                count,
+                max_neighbors: 4,
+            })
+        };
+
+        assert_eq!(
+            array(LifeRule::One(0)),
+            Ok(vec![true, false, false, false, false])
+        );
+        assert_eq!(
+            array(LifeRule::One(4)),
+            Ok(vec![false, false, false, false, true])
+        );
+        assert_eq!(array(LifeRule::One(5)), error(5));
+
+        assert_eq!(
+            array(LifeRule::Range(2..4)),
+            Ok(vec![false, false, true, true, false])
+        );
+        assert_eq!(array(LifeRule::Range(3..3)), Ok(vec![false; 5]));
+        assert_eq!(array(LifeRule::Range(3..usize::MAX)), error(5));
+
+        assert_eq!(
+            array(LifeRule::Numbers(&[4, 1])),
+            Ok(vec![false, true, false, false, true])
+        );
+        assert_eq!(array(LifeRule::Numbers(&[])), Ok(vec![false; 5]));
+        assert_eq!(array(LifeRule::Numbers(&[1, 9, 6])), error(9));
+
+        let mut raw = [false; 9];
+        raw[3] = true;
+        assert_eq!(
+            array(LifeRule::Raw(raw)),
+            Ok(vec![false, false, false, true, false])
+        );
+        raw[8] = true;
+        assert_eq!(array(LifeRule::Raw(raw)), error(8));
+
+        assert_eq!(array(LifeRule::All), Ok(vec![true; 5]));
+        assert_eq!(array(LifeRule::None), Ok(vec![false; 5]));
+
+        // Seeds: every cell dies, and cells with two living neighbors are born.
+        let seeds = Rule::new(LifeRule::None, LifeRule::One(2), 1, NeighborRule::Moore).unwrap();
+        assert_eq!(seeds, Rule::from_str("B2/S").unwrap());
+        let all = Rule::new(LifeRule::All, LifeRule::None, 1, NeighborRule::Hex).unwrap();
+        assert!((0..=6).all(|count| all.survives(count)));
+    }
+
+    #[test]
+    fn rule_out_of_range() {
+        let error = |count, max_neighbors| {
+            Err(GolError::InvalidNeighborCount {
+                count,
+                max_neighbors,
+            })
+        };
+        let two = LifeRule::One(2);
+        assert_eq!(
+            Rule::new(two.clone(), LifeRule::One(9), 1, NeighborRule::Moore),
+            error(9, 8)
+        );
+        assert_eq!(
+            Rule::new(
+                LifeRule::Range(3..10),
+                two.clone(),
+                1,
+                NeighborRule::VonNeumann
+            ),
+            error(5, 4)
+        );
+        assert_eq!(
+            Rule::new(
+                two.clone(),
+                LifeRule::Numbers(&[2, 7]),
+                1,
+                NeighborRule::Hex
+            ),
+            error(7, 6)
+        );
+        let mut raw = [false; 9];
+        raw[5] = true;
+        assert_eq!(
+            Rule::new(LifeRule::Raw(raw), two.clone(), 1, NeighborRule::VonNeumann),
+            error(5, 4)
+        );
+
+        // Reachable counts are fine, and the arrays still cover 0 to 8 neighbors.
+        let rule = Rule::new(
+            LifeRule::Range(0..5),
+            two.clone(),
             1,
             NeighborRule::VonNeumann,
+        )
+        .unwrap();
+        assert_eq!(
+            rule.survival,
+            [true, true, true, true, true, false, false, false, false]
+        );
+        // Moore with every neighbor counting twice
+        let kernel = NeighborRule::custom(Array2::from_shape_fn((3, 3), |(x, y)| {
+            if (x, y) == (1, 1) {
+                0
+            } else {
+                2
+            }
+        }))
+        .unwrap();
+        assert!(Rule::new(LifeRule::One(16), two, 1, kernel).is_ok());
+    }
+
+    #[test]
+    fn rule_from_str() {
+        let conway = Rule::default();
+        assert_eq!(Rule::from_str("B3/S23"), Ok(conway.clone()));
+        assert_eq!(Rule::from_str("s23/b3"), Ok(conway.clone()));
+        assert_eq!(Rule::from_str("23/3"), Ok(conway.clone()));
+        assert_eq!(Rule::from_str("23/3/2"), Ok(conway));
+
+        let brians_brain = Rule::from_str("/2/3").unwrap();
+        assert_eq!(brians_brain.survival, [false; 9]);
+        assert_eq!(
+            brians_brain.birth,
+            [false, false, true, false, false, false, false, false, false]
         );
-        let rules_conv = rules_std.clone();
+        assert_eq!(brians_brain.state, 2);
+        assert_eq!(Rule::from_str("B2/S/C3"), Ok(brians_brain));
+
+        assert_eq!(Rule::from_str("23/3/8").unwrap().state, 7);
+
+        // Every rule is written in the canonical form, which parses back into it.
+        for (input, canonical) in [
+            ("32/3", "B3/S23"),
+            ("s32/B63", "B36/S23"),
+            ("B2/S/C3", "B2/S/C3"),
+            ("/2/2", "B2/S"),
+            ("1357/1357/4", "B1357/S1357/C4"),
+        ] {
+            let rule = Rule::from_str(input).unwrap();
+            assert_eq!(rule.to_string(), canonical);
+            assert_eq!(Rule::from_str(canonical), Ok(rule));
+        }
+        for (name, rulestring) in NAMED_RULES {
+            assert_eq!(Rule::from_str(rulestring).unwrap().name(), Some(name));
+        }
+        let mut conway = Rule::default();
+        assert_eq!(conway.name(), Some("Conway's Life"));
+        conway.neighbor = NeighborRule::Hex;
+        assert_eq!(conway.name(), None);
+
+        for invalid in [
+            "",
+            "23",
+            "B3/S23/C3/4",
+            "B9/S23",
+            "B3/B3",
+            "23/3/1",
+            "23/3/4294967297",
+            "x/3",
+        ] {
+            assert_eq!(Rule::from_str(invalid), Err(()), "{invalid}");
+        }
+    }
 
-        let mut gol_std = GameOfLifeStd::new(field_std, rules_std);
-        let mut gol_conv = GameOfLifeConvolution::new(field_conv, rules_conv);
+    #[test]
+    fn birth_only_for_dead_cells() {
+        // The center of the row has 2 living neighbors, which is a birth count but no survival count.
+        let row = |ends: u8, center: u8| {
+            Array2::from_shape_fn((5, 5), |(x, y)| match (x, y) {
+                (1 | 3, 2) => ends,
+                (2, 2) => center,
+                _ => 0,
+            })
+        };
+        // The living center of B2/S dies, and the dying center of /2/3 decays instead of being born again.
+        for (rule, field) in [("B2/S", row(1, 1)), ("/2/3", row(2, 1))] {
+            let rules = Rule::from_str(rule).unwrap();
+            let mut gol_std = GameOfLifeStd::new(field.clone(), rules.clone());
+            let mut gol_conv = GameOfLifeConvolution::new(field, rules);
+            gol_std.compute_next_generation();
+            gol_conv.compute_next_generation();
+            assert_eq!(gol_std.cell(2, 2), Some(0), "{rule}");
+            assert_eq!(gol_conv.cell(2, 2), Some(0), "{rule}");
+        }
+    }
 
+    #[test]
+    fn algorithms_multi_state() {
+        let mut rng = rand::thread_rng();
+        let rules = Rule::from_str("23/3/8").unwrap();
+        let field = Array2::from_shape_simple_fn((20, 20), || rng.gen_range(0..=rules.state as u8));
+
+        let mut gol_std = GameOfLifeStd::new(field.clone(), rules.clone());
+        let mut gol_conv = GameOfLifeConvolution::new(field, rules);
+
+        for generation in 0..10 {
+            gol_std.compute_next_generation();
+            gol_conv.compute_next_generation();
+            assert_eq!(
+                gol_std.field,
+                gol_conv.field,
+                "standard and convolution differ after {} iterations",
+                generation + 1
+            );
+        }
+    }
+
+    /// One generation of the element-wise array arithmetic the convolution backend used before the update was fused,
+    /// with births limited to dead cells as in the fused update.
+    fn arithmetic_generation(field: &Array2<u8>, counts: &Array2<u8>, rules: &Rule) -> Array2<u8> {
+        let state = rules.state as u8;
+        let walls = field.map(|elem| *elem == u8::WALL);
+        let field = field.map(|elem| if *elem == u8::WALL { 0 } else { *elem });
+
+        let survive = counts.map(|elem| rules.survives(*elem as usize) as u8)
+            * field.map(|elem| (*elem == state) as u8);
+        let birth = counts.map(|elem| rules.is_born(*elem as usize) as u8)
+            * field.map(|elem| (*elem == 0) as u8);
+        let mut next = &survive * state
+            + (&field * &survive.map(|elem| 1 - elem)).map(|elem| elem.saturating_sub(1));
+        next += &(next.map(|elem| state - elem) * birth);
+        Zip::from(&mut next).and(&walls).for_each(|elem, &wall| {
+            if wall {
+                *elem = u8::WALL;
+            }
+        });
+        next
+    }
+
+    #[test]
+    fn convolution_matches_arithmetic() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for neighbor in [
+            NeighborRule::Moore,
+            NeighborRule::VonNeumann,
+            NeighborRule::Hex,
+        ] {
+            for border in [
+                BorderRule::Constant,
+                BorderRule::Nearest,
+                BorderRule::Mirror,
+                BorderRule::Reflect,
+                BorderRule::Wrap,
+            ] {
+                let mut rules = Rule::from_str("B2/S23/C5").unwrap();
+                rules.neighbor = neighbor.clone();
+                let field = Array2::from_shape_simple_fn((20, 15), || match rng.gen_range(0..10) {
+                    0 => u8::WALL,
+                    cell => cell % 5,
+                });
+                let mut expected = field.clone();
+                let mut gol = GameOfLifeConvolution::new(field, rules.clone()).with_border(border);
+                for generation in 0..15 {
+                    expected = arithmetic_generation(&expected, &gol.neighbor_counts(), &rules);
+                    gol.compute_next_generation();
+                    assert_eq!(
+                        gol.snapshot(),
+                        expected,
+                        "{neighbor:?} with {border:?} differs after {} iterations",
+                        generation + 1
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cell_types() {
+        // Rules that fit into `u8` evolve the same in wider cells.
+        let mut rng = StdRng::seed_from_u64(5);
+        let rules = Rule::from_str("B3/S23/C6").unwrap();
+        let field = GameOfLifeStd::<u8>::random(20, 15, rules.clone(), 0.4, &mut rng).snapshot();
+        let narrow = GameOfLifeConvolution::new(field.clone(), rules.clone());
+        let wide = GameOfLifeConvolution::new(field.mapv(u16::from), rules);
+        for (narrow, wide) in narrow.generations().zip(wide.generations()).take(10) {
+            assert_eq!(narrow.mapv(u16::from), wide);
+        }
+
+        // More states than `u8` has room for.
+        let rules = Rule::from_str("23/3/1000").unwrap();
+        assert_eq!(rules.state, 999);
         assert_eq!(
-            gol_std.field.map(|elem| elem.load(Ordering::Relaxed)),
-            gol_conv.field,
-            "standard and convolution differ"
+            check_state::<u8>(rules.state),
+            Err(GolError::InvalidState {
+                state: 999,
+                wall: 255
+            })
         );
+        let field = GameOfLifeStd::<u16>::random(20, 15, rules.clone(), 0.4, &mut rng).snapshot();
+        let mut gol_std = GameOfLifeStd::new(field.clone(), rules.clone());
+        let mut gol_conv = GameOfLifeConvolution::new(field, rules);
+        for generation in 0..300 {
+            assert_eq!(
+                gol_std.snapshot(),
+                gol_conv.snapshot(),
+                "standard and convolution differ after {generation} iterations"
+            );
+            gol_std.compute_next_generation();
+            gol_conv.compute_next_generation();
+        }
+        // Cells that died decay for longer than 255 generations.
+        assert!(gol_std
+            .snapshot()
+            .iter()
+            .any(|&cell| (1..744).contains(&cell)));
+
+        let mut walled = GameOfLifeStd::new(Array2::from_elem((3, 3), u16::WALL), Rule::default());
+        assert!(!walled.compute_next_generation());
+        assert_eq!(walled.to_ascii(), "XXX\nXXX\nXXX");
+    }
 
-        gol_std.compute_next_generation();
-        gol_conv.compute_next_generation();
+    #[test]
+    #[should_panic]
+    fn state_too_large() {
+        GameOfLifeStd::<u8>::new(Array2::zeros((5, 5)), Rule::from_str("23/3/256").unwrap());
+    }
+
+    #[test]
+    fn random() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let rules = Rule::from_str("23/3/5").unwrap();
+        let gol_std = GameOfLifeStd::<u8>::random(200, 100, rules.clone(), 0.3, &mut rng);
+        let gol_conv = GameOfLifeConvolution::random(200, 100, rules, 0.3, &mut rng);
+
+        for gol in [gol_std.snapshot(), gol_conv.snapshot()] {
+            assert_eq!(gol.dim(), (200, 100));
+            assert!(gol.iter().all(|&cell| cell == 0 || cell == 4));
+            let fraction = gol.iter().filter(|&&cell| cell == 4).count() as f64 / 20000.;
+            assert!((fraction - 0.3).abs() < 0.02, "{fraction}");
+        }
 
         assert_eq!(
-            gol_std.field.map(|elem| elem.load(Ordering::Relaxed)),
-            gol_conv.field,
-            "standard and convolution differ after one iteration"
+            GameOfLifeStd::<u8>::default().to_ascii(),
+            GameOfLifeConvolution::<u8>::default().to_ascii()
+        );
+        assert_eq!(
+            GameOfLifeStd::<u8>::default().snapshot(),
+            Array2::<u8>::zeros((10, 10))
         );
     }
 }
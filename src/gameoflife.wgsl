@@ -0,0 +1,74 @@
+// Computes one generation of a Game of Life, following `GameOfLifeStd`.
+// The field is stored column by column, so the cell at (x, y) lies at `x * numy + y`.
+
+struct Params {
+    numx: u32,
+    numy: u32,
+    state: u32,
+    // 0: von Neumann, 1: Moore, 2: hexagonal
+    neighbor: u32,
+    // Bit n is set if a cell with n living neighbors survives or is born, respectively.
+    survival: u32,
+    birth: u32,
+    // 1 if a living cell counts itself
+    include_center: u32,
+}
+
+const WALL: u32 = 255u;
+const VON_NEUMANN: u32 = 0u;
+const HEX: u32 = 2u;
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> current: array<u32>;
+@group(0) @binding(2) var<storage, read_write> next: array<u32>;
+// Set to 1 if any cell changes, reset before every generation.
+@group(0) @binding(3) var<storage, read_write> changed: atomic<u32>;
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let x = id.x;
+    let y = id.y;
+    if x >= params.numx || y >= params.numy {
+        return;
+    }
+
+    let index = x * params.numy + y;
+    let cell = current[index];
+    if cell == WALL {
+        next[index] = WALL;
+        return;
+    }
+
+    var count = select(0u, 1u, params.include_center == 1u && cell == params.state);
+    for (var dx = -1; dx <= 1; dx++) {
+        for (var dy = -1; dy <= 1; dy++) {
+            if (dx == 0 && dy == 0) || (params.neighbor == VON_NEUMANN && dx != 0 && dy != 0) {
+                continue;
+            }
+            // Odd rows are shifted to the right, so the rows above and below lack one diagonal neighbor.
+            if params.neighbor == HEX && dy != 0 && dx == select(1, -1, y % 2u == 1u) {
+                continue;
+            }
+            let nx = i32(x) + dx;
+            let ny = i32(y) + dy;
+            if nx < 0 || ny < 0 || nx >= i32(params.numx) || ny >= i32(params.numy) {
+                continue;
+            }
+            if current[u32(nx) * params.numy + u32(ny)] == params.state {
+                count += 1u;
+            }
+        }
+    }
+
+    var value = 0u;
+    if (cell == 0u && ((params.birth >> count) & 1u) == 1u)
+        || (cell == params.state && ((params.survival >> count) & 1u) == 1u) {
+        value = params.state;
+    } else if cell != 0u {
+        value = cell - 1u;
+    }
+    next[index] = value;
+    if value != cell {
+        atomicStore(&changed, 1u);
+    }
+}
@@ -0,0 +1,398 @@
+//! # GPU
+//! Contains a Game of Life computed by a compute shader with [`wgpu`](https://wgpu.rs/).
+
+use std::cell::{Ref, RefCell};
+
+use ndarray::Array2;
+use wgpu::util::DeviceExt;
+
+use crate::gameoflife::{check_dimensions, check_state, Board, GameOfLife, NeighborRule, Rule};
+
+/// Number of cells per dimension handled by one workgroup of the shader.
+const WORKGROUP_SIZE: u32 = 8;
+
+/// Computes the time steps on the GPU.
+///
+/// The field is kept in two storage buffers, one of which is read while the next generation is written into the other.
+/// Cells are only read back from the GPU when they are accessed and then cached until the next generation.
+pub struct GameOfLifeGpu {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+    buffers: [wgpu::Buffer; 2],
+    bind_groups: [wgpu::BindGroup; 2],
+    staging: wgpu::Buffer,
+    changed: wgpu::Buffer,
+    changed_staging: wgpu::Buffer,
+    current: usize,
+    cache: RefCell<Option<Array2<u8>>>,
+    rules: Rule,
+    numx: usize,
+    numy: usize,
+}
+
+impl GameOfLifeGpu {
+    /// Generate a new Game of Life on the default GPU.
+    /// Returns None if no GPU is available.
+    ///
+    /// # Panics
+    /// Panics if the field has no cells, the state does not fit into a `u8`, or the rule uses `NeighborRule::Custom` or `NeighborRule::VonNeumannR`, which the shader does not support.
+    pub fn try_new(field: Array2<u8>, rules: Rule) -> Option<Self> {
+        let (numx, numy) = field.dim();
+        if let Err(e) = check_dimensions(numx, numy, usize::MAX) {
+            panic!("{e}");
+        }
+        if let Err(e) = check_state::<u8>(rules.state) {
+            panic!("{e}");
+        }
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))?;
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                required_limits: adapter.limits(),
+                ..Default::default()
+            },
+            None,
+        ))
+        .ok()?;
+
+        Some(Self::with_device(device, queue, field, rules))
+    }
+
+    fn with_device(
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        field: Array2<u8>,
+        rules: Rule,
+    ) -> Self {
+        let (numx, numy) = field.dim();
+
+        let params = [
+            numx as u32,
+            numy as u32,
+            rules.state,
+            match rules.neighbor {
+                NeighborRule::VonNeumann => 0,
+                NeighborRule::Moore => 1,
+                NeighborRule::Hex => 2,
+                NeighborRule::Custom(_) | NeighborRule::VonNeumannR(_) => {
+                    panic!("custom kernels and larger radii are not supported on the GPU")
+                }
+            },
+            bitmask(&rules.survival),
+            bitmask(&rules.birth),
+            rules.include_center as u32,
+            0,
+        ];
+        let params = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("params"),
+            contents: &to_bytes(params.into_iter()),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let cells = to_bytes(field.iter().map(|&cell| cell as u32));
+        let usage = wgpu::BufferUsages::STORAGE
+            | wgpu::BufferUsages::COPY_SRC
+            | wgpu::BufferUsages::COPY_DST;
+        let buffers = [
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("field 0"),
+                contents: &cells,
+                usage,
+            }),
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("field 1"),
+                contents: &cells,
+                usage,
+            }),
+        ];
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("staging"),
+            size: cells.len() as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let changed = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("changed"),
+            size: 4,
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_SRC
+                | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let changed_staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("changed staging"),
+            size: 4,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let module = device.create_shader_module(wgpu::include_wgsl!("gameoflife.wgsl"));
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("generation"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        // Bind group i reads buffer i and writes the other one.
+        let layout = pipeline.get_bind_group_layout(0);
+        let bind_groups = [0, 1].map(|i| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: params.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: buffers[i].as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: buffers[1 - i].as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: changed.as_entire_binding(),
+                    },
+                ],
+            })
+        });
+
+        Self {
+            device,
+            queue,
+            pipeline,
+            buffers,
+            bind_groups,
+            staging,
+            changed,
+            changed_staging,
+            current: 0,
+            cache: RefCell::new(Some(field)),
+            rules,
+            numx,
+            numy,
+        }
+    }
+
+    /// Copies the current field from the GPU.
+    fn read_back(&self) -> Array2<u8> {
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        encoder.copy_buffer_to_buffer(
+            &self.buffers[self.current],
+            0,
+            &self.staging,
+            0,
+            self.staging.size(),
+        );
+        self.queue.submit([encoder.finish()]);
+
+        let cells = self
+            .map(&self.staging)
+            .into_iter()
+            .map(|cell| cell as u8)
+            .collect();
+        Array2::from_shape_vec((self.numx, self.numy), cells).unwrap()
+    }
+
+    /// Waits for the GPU and reads a staging buffer.
+    fn map(&self, staging: &wgpu::Buffer) -> Vec<u32> {
+        let slice = staging.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |result| {
+            result.expect("failed to read a buffer from the GPU")
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+
+        let values = slice
+            .get_mapped_range()
+            .chunks_exact(4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect();
+        staging.unmap();
+        values
+    }
+
+    /// Returns the current field, reading it back from the GPU if it is not cached.
+    fn field(&self) -> Ref<'_, Array2<u8>> {
+        if self.cache.borrow().is_none() {
+            self.cache.replace(Some(self.read_back()));
+        }
+        Ref::map(self.cache.borrow(), |cache| cache.as_ref().unwrap())
+    }
+}
+
+impl GameOfLife for GameOfLifeGpu {
+    type Data = u8;
+
+    /// Generate a new Game of Life on the default GPU.
+    ///
+    /// # Panics
+    /// Panics if no GPU is available, see [`GameOfLifeGpu::try_new`].
+    fn new(field: Array2<u8>, rules: Rule) -> Self {
+        Self::try_new(field, rules).expect("no GPU available")
+    }
+
+    /// Waits for the GPU to finish the generation in order to tell whether any cell changed.
+    fn compute_next_generation(&mut self) -> bool {
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        encoder.clear_buffer(&self.changed, 0, None);
+        {
+            let mut pass = encoder.begin_compute_pass(&Default::default());
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &self.bind_groups[self.current], &[]);
+            pass.dispatch_workgroups(
+                (self.numx as u32).div_ceil(WORKGROUP_SIZE),
+                (self.numy as u32).div_ceil(WORKGROUP_SIZE),
+                1,
+            );
+        }
+        encoder.copy_buffer_to_buffer(&self.changed, 0, &self.changed_staging, 0, 4);
+        self.queue.submit([encoder.finish()]);
+
+        self.current = 1 - self.current;
+        *self.cache.get_mut() = None;
+        self.map(&self.changed_staging)[0] != 0
+    }
+
+    fn cell(&self, x: usize, y: usize) -> Option<u8> {
+        if x >= self.numx || y >= self.numy {
+            return None;
+        }
+        Some(self.field()[[x, y]])
+    }
+
+    fn set_cell(&mut self, x: usize, y: usize, value: u8) -> bool {
+        if x >= self.numx || y >= self.numy {
+            return false;
+        }
+        self.queue.write_buffer(
+            &self.buffers[self.current],
+            ((x * self.numy + y) * 4) as u64,
+            &(value as u32).to_le_bytes(),
+        );
+        if let Some(field) = self.cache.get_mut() {
+            field[[x, y]] = value;
+        }
+        true
+    }
+
+    fn to_board(&self) -> Board {
+        Board {
+            numx: self.numx,
+            numy: self.numy,
+            cells: self.field().iter().copied().collect(),
+            rule: self.rules.clone(),
+        }
+    }
+
+    fn numx(&self) -> usize {
+        self.numx
+    }
+
+    fn numy(&self) -> usize {
+        self.numy
+    }
+
+    fn state(&self) -> u8 {
+        // Checked to fit when the game was created.
+        self.rules.state as u8
+    }
+
+    fn snapshot(&self) -> Array2<u8> {
+        self.field().clone()
+    }
+}
+
+impl Clone for GameOfLifeGpu {
+    /// Uploads a copy of the field to new buffers on the same GPU.
+    fn clone(&self) -> Self {
+        Self::with_device(
+            self.device.clone(),
+            self.queue.clone(),
+            self.snapshot(),
+            self.rules.clone(),
+        )
+    }
+}
+
+/// Packs a survival or birth array into a bitmask, where bit n corresponds to n living neighbors.
+fn bitmask(rule: &[bool]) -> u32 {
+    rule.iter()
+        .enumerate()
+        .fold(0, |mask, (count, &set)| mask | (set as u32) << count)
+}
+
+/// Converts values into the little-endian bytes expected by the GPU.
+fn to_bytes(values: impl Iterator<Item = u32>) -> Vec<u8> {
+    values.flat_map(u32::to_le_bytes).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::gameoflife::{GameOfLifeStd, LifeRule, WALL};
+
+    #[test]
+    #[ignore = "needs a GPU adapter, run with `cargo test --features gpu -- --ignored`"]
+    fn matches_std() {
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for (neighbor, include_center) in [
+            (NeighborRule::Moore, false),
+            (NeighborRule::VonNeumann, false),
+            (NeighborRule::Hex, false),
+            (NeighborRule::Moore, true),
+            (NeighborRule::Hex, true),
+        ] {
+            let rules = Rule {
+                include_center,
+                ..Rule::new(
+                    LifeRule::Raw([false, false, true, true, false, false, false, false, false]),
+                    LifeRule::Raw([false, false, false, true, false, false, false, false, false]),
+                    3,
+                    neighbor,
+                )
+                .unwrap()
+            };
+            let field = Array2::from_shape_simple_fn((40, 25), || match rng.gen_range(0..10) {
+                0..=2 => 3,
+                3 => 1,
+                4 => WALL,
+                _ => 0,
+            });
+
+            let mut gol_gpu = GameOfLifeGpu::try_new(field.clone(), rules.clone())
+                .expect("no GPU adapter available");
+            let mut gol_std = GameOfLifeStd::new(field, rules);
+
+            for generation in 0..20 {
+                assert_eq!(
+                    gol_gpu.snapshot(),
+                    gol_std.snapshot(),
+                    "standard and GPU differ after {generation} iterations"
+                );
+                assert_eq!(
+                    gol_std.compute_next_generation(),
+                    gol_gpu.compute_next_generation()
+                );
+            }
+
+            gol_std.set_cell(3, 4, WALL);
+            gol_gpu.set_cell(3, 4, WALL);
+            gol_std.compute_next_generation();
+            gol_gpu.compute_next_generation();
+            assert_eq!(gol_gpu.to_board(), gol_std.to_board());
+        }
+    }
+}
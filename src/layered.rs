@@ -0,0 +1,151 @@
+//! # Layered
+//! Contains two Games of Life on the same field that interact through a coupling.
+
+use ndarray::{Array2, Zip};
+
+use crate::gameoflife::*;
+
+/// Closure that [`LayeredGameOfLife`] calls with both layers after every generation, e.g. to let one layer suppress the other.
+pub type Coupling = Box<dyn FnMut(&mut GameOfLifeConvolution, &mut GameOfLifeConvolution)>;
+
+/// Two layers that compute their generations independently, each with its own rule, and then interact through a [`Coupling`].
+///
+/// ```
+/// use game_of_life::gameoflife::*;
+/// use game_of_life::layered::LayeredGameOfLife;
+/// use ndarray::Array2;
+///
+/// let block = Array2::from_shape_fn((6, 6), |(x, y)| (2..4).contains(&x) as u8 * (2..4).contains(&y) as u8);
+/// let a = GameOfLifeConvolution::new(block.clone(), Rule::default());
+/// let b = GameOfLifeConvolution::new(block, Rule::default());
+///
+/// // Living cells of the first layer kill the cells of the second one.
+/// let mut layers = LayeredGameOfLife::new(a, b).with_coupling(|a, b| {
+///     for ((x, y), &cell) in a.snapshot().indexed_iter() {
+///         if cell == a.state() {
+///             b.set_cell(x, y, 0);
+///         }
+///     }
+/// });
+/// layers.compute_next_generation();
+/// let (a, b) = layers.snapshot();
+/// assert_eq!(a.iter().filter(|&&cell| cell == 1).count(), 4);
+/// assert!(b.iter().all(|&cell| cell == 0));
+/// ```
+pub struct LayeredGameOfLife {
+    a: GameOfLifeConvolution,
+    b: GameOfLifeConvolution,
+    coupling: Option<Coupling>,
+}
+
+impl LayeredGameOfLife {
+    /// Panics if the layers have different dimensions.
+    pub fn new(a: GameOfLifeConvolution, b: GameOfLifeConvolution) -> Self {
+        assert_eq!(
+            (a.numx(), a.numy()),
+            (b.numx(), b.numy()),
+            "both layers need the same dimensions"
+        );
+        Self {
+            a,
+            b,
+            coupling: None,
+        }
+    }
+
+    /// Sets the coupling, without one the layers do not interact.
+    pub fn with_coupling(
+        mut self,
+        coupling: impl FnMut(&mut GameOfLifeConvolution, &mut GameOfLifeConvolution) + 'static,
+    ) -> Self {
+        self.coupling = Some(Box::new(coupling));
+        self
+    }
+
+    /// Computes the next generation of both layers, applies the coupling, and returns whether any cell of either layer changed.
+    pub fn compute_next_generation(&mut self) -> bool {
+        let before = self.snapshot();
+        self.a.compute_next_generation();
+        self.b.compute_next_generation();
+        if let Some(ref mut coupling) = self.coupling {
+            coupling(&mut self.a, &mut self.b);
+        }
+        self.snapshot() != before
+    }
+
+    pub fn layers(&self) -> (&GameOfLifeConvolution, &GameOfLifeConvolution) {
+        (&self.a, &self.b)
+    }
+
+    /// Returns the fields of both layers.
+    pub fn snapshot(&self) -> (Array2<u8>, Array2<u8>) {
+        (self.a.snapshot(), self.b.snapshot())
+    }
+
+    pub fn numx(&self) -> usize {
+        self.a.numx()
+    }
+
+    pub fn numy(&self) -> usize {
+        self.a.numy()
+    }
+
+    /// Renders the layers as RGB pixels row by row, the first one in the red and the second one in the green channel.
+    /// Cells are faded by their state like in the presentations, walls are at full brightness.
+    pub fn render(&self) -> Vec<u8> {
+        let channel = |cell: u8, state: u8| match cell {
+            WALL => u8::MAX,
+            _ => (255 * cell as u32 / state.max(1) as u32) as u8,
+        };
+        let (a, b) = self.snapshot();
+        let (state_a, state_b) = (self.a.state(), self.b.state());
+        let mut pixels = Vec::with_capacity(a.len() * 3);
+        // Transposed, as the fields are indexed by `[x, y]`.
+        Zip::from(a.t()).and(b.t()).for_each(|&a, &b| {
+            pixels.extend([channel(a, state_a), channel(b, state_b), 0]);
+        });
+        pixels
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn independent_layers() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let a = GameOfLifeConvolution::random(16, 12, Rule::default(), 0.4, &mut rng);
+        let b = GameOfLifeConvolution::random(
+            16,
+            12,
+            Rule::from_str("B36/S23").unwrap(),
+            0.4,
+            &mut rng,
+        );
+        let (mut alone_a, mut alone_b) = (a.clone(), b.clone());
+        let mut layers = LayeredGameOfLife::new(a, b).with_coupling(|_, _| {});
+
+        for _ in 0..10 {
+            alone_a.compute_next_generation();
+            alone_b.compute_next_generation();
+            layers.compute_next_generation();
+            assert_eq!(layers.snapshot(), (alone_a.snapshot(), alone_b.snapshot()));
+        }
+    }
+
+    #[test]
+    fn render_channels() {
+        let mut a = GameOfLifeConvolution::new(Array2::zeros((2, 1)), Rule::default());
+        let mut b = GameOfLifeConvolution::new(Array2::zeros((2, 1)), Rule::default());
+        a.set_cell(0, 0, 1);
+        b.set_cell(1, 0, WALL);
+        let layers = LayeredGameOfLife::new(a, b);
+        assert_eq!(layers.render(), [255, 0, 0, 0, 255, 0]);
+    }
+}
@@ -1,4 +1,13 @@
 //! This library is my attempt at implementing an advanced Conway's Game of Life in Rust. My goal was to optimize and overengineer it in order to learn Rust along the way.
 
+pub mod builder;
+pub mod formats;
 pub mod gameoflife;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod layered;
+pub mod patterns;
+#[cfg(feature = "presentation")]
 pub mod presentation;
+#[cfg(feature = "wasm")]
+pub mod wasm;
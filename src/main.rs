@@ -1,19 +1,77 @@
 use core::panic;
+use std::cell::RefCell;
 use std::fmt::Display;
 use std::fs::File;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::str::FromStr;
-use std::sync::atomic::AtomicU8;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use activity::Activity;
+use bench::Benchmark;
+#[cfg(feature = "checkpoint")]
+use checkpoint::{read_checkpoint, Checkpointer};
 use clap::{Parser, Subcommand};
+use game_of_life::builder::*;
+use game_of_life::formats::*;
 use game_of_life::gameoflife::*;
+use game_of_life::patterns::*;
 use game_of_life::presentation::*;
 use indicatif::{ProgressBar, ProgressStyle};
 use inquire::validator::Validation;
 use inquire::{required, Confirm, CustomType, InquireError, MultiSelect, Select, Text};
-use ndarray::{self, Array1};
-use rand::{self, Rng};
+use log::{Level, LevelFilter};
+use ndarray::{self, Array2};
+use noise::{Noise, Noisy};
+#[cfg(feature = "config")]
+use serde::Deserialize;
+use summary::{Summary, SummaryFormat};
+
+/// Forwards the methods of `GameOfLife` that a wrapper leaves unchanged to the game in its field `$gol`,
+/// so the wrapper only implements `new` and `compute_next_generation`.
+macro_rules! forward_game_of_life {
+    ($gol:ident) => {
+        fn to_board(&self) -> Board<Self::Data> {
+            self.$gol.to_board()
+        }
+
+        fn cell(&self, x: usize, y: usize) -> Option<Self::Data> {
+            self.$gol.cell(x, y)
+        }
+
+        fn set_cell(&mut self, x: usize, y: usize, value: Self::Data) -> bool {
+            self.$gol.set_cell(x, y, value)
+        }
+
+        fn numx(&self) -> usize {
+            self.$gol.numx()
+        }
+
+        fn numy(&self) -> usize {
+            self.$gol.numy()
+        }
+
+        fn state(&self) -> Self::Data {
+            self.$gol.state()
+        }
+
+        fn snapshot(&self) -> Array2<Self::Data> {
+            self.$gol.snapshot()
+        }
+
+        fn neighbor_counts(&self) -> Array2<u8> {
+            self.$gol.neighbor_counts()
+        }
+    };
+}
+
+mod activity;
+mod bench;
+#[cfg(feature = "checkpoint")]
+mod checkpoint;
+mod noise;
+mod summary;
 
 /// CLI Parser using `clap`
 #[derive(Parser)]
@@ -23,12 +81,12 @@ struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
 
-    /// Number of iterations
-    #[arg(short, long)]
+    /// Number of iterations, 0 runs the TUI until quit
+    #[arg(short, long, global = true)]
     iterations: Option<usize>,
 
-    /// Time per iteration (in ms)
-    #[arg(short, long)]
+    /// Time per iteration (in ms), GIFs have a granularity of 10 ms
+    #[arg(short, long, global = true)]
     timeiter: Option<u32>,
 
     /// x dimension of the field
@@ -39,21 +97,278 @@ struct Cli {
     #[arg(short)]
     y: Option<u32>,
 
-    /// Algorithm (standard or convolution)
+    /// Algorithm (standard, convolution, or gpu with the gpu feature)
     #[arg(short, long)]
     algorithm: Option<String>,
 
-    /// Neighbor algorithm (Moore or VonNeumann)
+    /// Neighbor algorithm (Moore, VonNeumann, Hex, or vn followed by a radius like vn2)
     #[arg(short, long)]
     neighbor: Option<String>,
 
+    /// Text or CSV file with a kernel of neighbor weights to use instead of the neighbor algorithm
+    #[arg(long, conflicts_with = "neighbor")]
+    kernel: Option<PathBuf>,
+
+    /// Border rule (constant, nearest, mirror, reflect, or wrap), the standard algorithm only supports constant
+    #[arg(short, long)]
+    border: Option<String>,
+
     /// Probability of living cells in the initial field
     #[arg(short, long)]
     probability: Option<f32>,
 
-    /// Number of iterations before a cell dies
+    /// Seed of the random initial field, the same seed always produces the same field
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Grayscale PNG that scales the probability of living cells in the random initial field, black keeps -p and white has none
+    #[arg(long)]
+    density_map: Option<PathBuf>,
+
+    /// Symmetry of the random initial field (none, horizontal, vertical, both, or rotational)
+    #[arg(long)]
+    symmetry: Option<String>,
+
+    /// Values of the living cells of the random initial field (max or uniform for the state, or random between 1 and the state)
+    #[arg(long)]
+    init_states: Option<String>,
+
+    /// Number of iterations before a cell dies, overrides the number of states of the rule
+    #[arg(short, long)]
+    state: Option<u8>,
+
+    /// Rulestring in B/S (e.g. B3/S23) or S/B/C (e.g. 23/3/8) notation
+    #[arg(short, long)]
+    rule: Option<String>,
+
+    /// PNG, plaintext (.cells), RLE (.rle), or Life 1.06 (.lif) file to load the initial field from, dark pixels, O, o, or the listed coordinates are alive
+    #[arg(long)]
+    image: Option<PathBuf>,
+
+    /// Luminance (0-255) below which pixels of the image are alive
+    #[arg(long)]
+    threshold: Option<u8>,
+
+    /// Pattern to place in the center instead of a random field (glider, blinker, lwss, pulsar, or gosper-glider-gun)
+    #[arg(long, conflicts_with = "image")]
+    seed_pattern: Option<String>,
+
+    /// Plaintext (.cells) file to save the initial field to, which can be loaded again with --image
+    #[arg(long)]
+    dump_initial: Option<PathBuf>,
+
+    /// Number of dead cells around the random field, the image, or the pattern on every side, which adds to the size of the field
+    #[arg(long)]
+    margin: Option<u32>,
+
+    /// Width and height of every cell in pixels for the GIF, PNG, and video output
+    #[arg(long, global = true)]
+    scale: Option<u32>,
+
+    /// Fit as many cells as possible into WIDTHxHEIGHT pixels of the GIF, PNG, or video output
+    #[arg(long, conflicts_with_all = ["x", "y"])]
+    resolution: Option<String>,
+
+    /// Ratio of the width to the height of every cell in the GIF as WIDTH:HEIGHT, e.g. 1:2 like the terminal
+    #[arg(long)]
+    aspect: Option<String>,
+
+    /// Maximum size of the GIF in MB, after which it is saved with fewer generations
+    #[arg(long)]
+    max_size: Option<f64>,
+
+    /// Time in ms to show the last frame of the GIF before it loops
+    #[arg(long)]
+    hold_last: Option<u32>,
+
+    /// Number of frames over which cells fade out after they die, 0 disables the trail
+    #[arg(long)]
+    trail: Option<u8>,
+
+    /// Flip every cell with PROBABILITY after every GENERATIONS generations as PROBABILITY:GENERATIONS, e.g. 0.01:10, reproducible with --seed
+    #[arg(long)]
+    noise: Option<String>,
+
+    /// Draw the TUI without colors, only telling the states of the cells apart by their glyphs
+    #[arg(long)]
+    no_color: bool,
+
+    /// Stop once a generation does not change the field, the TUI pauses instead
+    #[arg(long)]
+    stop_when_stable: bool,
+
+    /// Number of previous generations the TUI keeps to step back to with 'b' (default 100)
+    #[arg(long)]
+    history: Option<usize>,
+
+    /// Print a summary of the run as text, or as JSON with --summary=json, once a GIF, PNG sequence, or video is saved
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "text")]
+    summary: Option<String>,
+
+    /// PNG to save a heat map of how often every cell was alive to once the run ends
+    #[arg(long)]
+    activity: Option<PathBuf>,
+
+    /// File to save the field, rule, and generation to every --checkpoint-every generations, which --resume continues from
+    #[cfg(feature = "checkpoint")]
+    #[arg(long)]
+    checkpoint: Option<PathBuf>,
+
+    /// Number of generations between two checkpoints (default 100)
+    #[cfg(feature = "checkpoint")]
+    #[arg(long)]
+    checkpoint_every: Option<usize>,
+
+    /// Checkpoint to continue from instead of generating a field, -i counts the generations after the checkpoint
+    #[cfg(feature = "checkpoint")]
+    #[arg(long)]
+    resume: Option<PathBuf>,
+
+    /// Number of threads for the standard algorithm, 1 computes serially for deterministic timing (default: one per core)
+    #[arg(long)]
+    threads: Option<usize>,
+
+    /// Only print errors, no warnings, messages, or progress bars
+    #[arg(short, long, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Also print how long every generation takes
     #[arg(short, long)]
+    verbose: bool,
+
+    /// TOML file with default values for the flags, which take precedence
+    #[cfg(feature = "config")]
+    #[arg(long)]
+    config: Option<PathBuf>,
+}
+
+/// Values of the flags read from a TOML file, where each key is the long name of a flag.
+#[cfg(feature = "config")]
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct Config {
+    iterations: Option<usize>,
+    timeiter: Option<u32>,
+    x: Option<u32>,
+    y: Option<u32>,
+    algorithm: Option<String>,
+    neighbor: Option<String>,
+    kernel: Option<PathBuf>,
+    border: Option<String>,
+    probability: Option<f32>,
+    seed: Option<u64>,
+    density_map: Option<PathBuf>,
+    symmetry: Option<String>,
+    init_states: Option<String>,
     state: Option<u8>,
+    rule: Option<String>,
+    image: Option<PathBuf>,
+    threshold: Option<u8>,
+    seed_pattern: Option<String>,
+    dump_initial: Option<PathBuf>,
+    margin: Option<u32>,
+    scale: Option<u32>,
+    resolution: Option<String>,
+    aspect: Option<String>,
+    max_size: Option<f64>,
+    hold_last: Option<u32>,
+    trail: Option<u8>,
+    noise: Option<String>,
+    no_color: Option<bool>,
+    stop_when_stable: Option<bool>,
+    history: Option<usize>,
+    summary: Option<String>,
+    activity: Option<PathBuf>,
+    #[cfg(feature = "checkpoint")]
+    checkpoint: Option<PathBuf>,
+    #[cfg(feature = "checkpoint")]
+    checkpoint_every: Option<usize>,
+    #[cfg(feature = "checkpoint")]
+    resume: Option<PathBuf>,
+    threads: Option<usize>,
+}
+
+#[cfg(feature = "config")]
+impl Cli {
+    /// Reads the file given by `--config` and fills in all flags that were not given.
+    /// Terminates if the file cannot be read or parsed.
+    fn with_config(mut self) -> Self {
+        let Some(ref path) = self.config else {
+            return self;
+        };
+        let config = match std::fs::read_to_string(path) {
+            Ok(content) => match toml::from_str(&content) {
+                Ok(config) => config,
+                Err(e) => {
+                    eprintln!("Invalid config {}: {e}\nAborting...", path.display());
+                    std::process::exit(exitcode::CONFIG);
+                }
+            },
+            Err(e) => {
+                eprintln!(
+                    "Could not read the config {}: {e}\nAborting...",
+                    path.display()
+                );
+                std::process::exit(exitcode::NOINPUT);
+            }
+        };
+        self.merge(config);
+        self
+    }
+
+    /// Fills in all flags that were not given from the config.
+    fn merge(&mut self, config: Config) {
+        self.iterations = self.iterations.or(config.iterations);
+        self.timeiter = self.timeiter.or(config.timeiter);
+        // The resolution and the dimensions exclude each other, so the config only fills in whichever the flags leave open.
+        if self.resolution.is_none() {
+            self.x = self.x.or(config.x);
+            self.y = self.y.or(config.y);
+            if self.x.is_none() && self.y.is_none() {
+                self.resolution = config.resolution;
+            }
+        }
+        self.algorithm = self.algorithm.take().or(config.algorithm);
+        // The kernel and the neighbor algorithm exclude each other, so only use the config's if neither was given.
+        if self.neighbor.is_none() && self.kernel.is_none() {
+            self.neighbor = config.neighbor;
+            self.kernel = config.kernel;
+        }
+        self.border = self.border.take().or(config.border);
+        self.probability = self.probability.or(config.probability);
+        self.seed = self.seed.or(config.seed);
+        self.density_map = self.density_map.take().or(config.density_map);
+        self.symmetry = self.symmetry.take().or(config.symmetry);
+        self.init_states = self.init_states.take().or(config.init_states);
+        self.state = self.state.or(config.state);
+        self.rule = self.rule.take().or(config.rule);
+        self.threshold = self.threshold.or(config.threshold);
+        // The image and the pattern exclude each other, so only use the config's if neither was given.
+        if self.image.is_none() && self.seed_pattern.is_none() {
+            self.image = config.image;
+            self.seed_pattern = config.seed_pattern;
+        }
+        self.dump_initial = self.dump_initial.take().or(config.dump_initial);
+        self.margin = self.margin.or(config.margin);
+        self.scale = self.scale.or(config.scale);
+        self.aspect = self.aspect.take().or(config.aspect);
+        self.max_size = self.max_size.or(config.max_size);
+        self.hold_last = self.hold_last.or(config.hold_last);
+        self.trail = self.trail.or(config.trail);
+        self.noise = self.noise.take().or(config.noise);
+        self.no_color |= config.no_color.unwrap_or(false);
+        self.stop_when_stable |= config.stop_when_stable.unwrap_or(false);
+        self.history = self.history.or(config.history);
+        self.summary = self.summary.take().or(config.summary);
+        self.activity = self.activity.take().or(config.activity);
+        #[cfg(feature = "checkpoint")]
+        {
+            self.checkpoint = self.checkpoint.take().or(config.checkpoint);
+            self.checkpoint_every = self.checkpoint_every.or(config.checkpoint_every);
+            self.resume = self.resume.take().or(config.resume);
+        }
+        self.threads = self.threads.or(config.threads);
+    }
 }
 
 /// Subcommands of CLI Parser
@@ -61,36 +376,44 @@ struct Cli {
 enum Commands {
     /// Prints the Game of Life in a GIF, takes file name of GIF
     Gif { output: String },
-    /// Prints the Game of Life in the terminal, press 'q' to exit
+    /// Saves every generation of the Game of Life as a PNG, takes the output directory
+    Png { output: String },
+    /// Saves a single generation of the Game of Life as a PNG, takes the file name of the PNG
+    Snapshot {
+        output: String,
+        /// Generation to save, counted from 0 for the initial field, at most the number of iterations if one is given
+        #[arg(long)]
+        at: usize,
+    },
+    /// Encodes the Game of Life as a video with ffmpeg, takes the file name (.mp4, .webm, or .mkv)
+    Video { output: String },
+    /// Converts an RLE pattern into a GIF on a field that fits it, takes the pattern and the file name of the GIF
+    Rle2gif {
+        input: PathBuf,
+        output: String,
+        /// Number of dead cells around the pattern on every side
+        #[arg(long, default_value_t = 0)]
+        padding: u32,
+    },
+    /// Prints the Game of Life in the terminal, press 'q' to exit, space to pause, 'n' to step, '+'/'-' to change the speed, 'b' to step back, 'r' to reset, the arrow keys to move the view across large fields, and click to toggle cells
     Tui,
+    /// Computes the generations without presenting them and prints how many generations per second the algorithm managed
+    Bench,
+    /// Checks a rulestring and prints its canonical form, its name if it is well-known, and its survival and birth arrays
+    Rule { rulestring: String },
 }
 
-#[derive(Debug)]
-/// Available algorithms to calculate the time steps
-enum Algorithm {
-    Std,
-    Conv,
+/// Choices for the initial field in the dialogue
+enum Initialization {
+    Random,
+    Pattern(Pattern),
 }
 
-impl FromStr for Algorithm {
-    type Err = ();
-
-    fn from_str(input: &str) -> Result<Algorithm, Self::Err> {
-        match input.to_lowercase().as_str() {
-            "std" => Ok(Algorithm::Std),
-            "standard" => Ok(Algorithm::Std),
-            "conv" => Ok(Algorithm::Conv),
-            "convolution" => Ok(Algorithm::Conv),
-            _ => Err(()),
-        }
-    }
-}
-
-impl Display for Algorithm {
+impl Display for Initialization {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match *self {
-            Algorithm::Std => write!(f, "standard"),
-            Algorithm::Conv => write!(f, "convolution"),
+            Initialization::Random => write!(f, "random"),
+            Initialization::Pattern(pattern) => write!(f, "{pattern}"),
         }
     }
 }
@@ -101,11 +424,38 @@ struct Arguments {
     output_file: Option<PathBuf>,
     iterations: usize,
     time_per_iteration: Duration,
+    stop_when_stable: bool,
+    history: usize,
+    color: bool,
+    summary: Option<SummaryFormat>,
+    activity: Option<PathBuf>,
+    /// File and number of generations between two checkpoints
+    #[cfg(feature = "checkpoint")]
+    checkpoint: Option<(PathBuf, usize)>,
+    /// Number of generations before the initial field, which is only not 0 when resuming
+    #[cfg(feature = "checkpoint")]
+    generation: usize,
+    threads: Option<usize>,
+    scale: usize,
+    aspect: Aspect,
+    max_size: Option<u64>,
+    hold_last: Option<Duration>,
+    trail: u8,
+    noise: Option<Noise>,
     numx: u32,
     numy: u32,
     algorithm: Algorithm,
     rule: Rule,
+    border: BorderRule,
     probability: f32,
+    seed: Option<u64>,
+    density_map: Option<Array2<f64>>,
+    symmetry: Symmetry,
+    init_states: InitStates,
+    initial_field: Option<Array2<u8>>,
+    /// Number of dead cells around the initial field
+    margin: usize,
+    dump_initial: Option<PathBuf>,
     progressbar: Option<ProgressBar>,
 }
 
@@ -118,10 +468,12 @@ impl Arguments {
             Some(ref algorithm_string) => match Algorithm::from_str(algorithm_string) {
                 Ok(algorithm) => algorithm,
                 Err(_) => {
+                    let algorithms: Vec<String> =
+                        Algorithm::all().iter().map(Algorithm::to_string).collect();
+                    let (last, rest) = algorithms.split_last().unwrap();
                     eprintln!(
-                        "Invalid algorithm.\nPlease choose from {}, or {}.\nAborting...",
-                        Algorithm::Std,
-                        Algorithm::Conv,
+                        "Invalid algorithm.\nPlease choose from {}, or {last}.\nAborting...",
+                        rest.join(", "),
                     );
                     std::process::exit(exitcode::CONFIG);
                 }
@@ -134,75 +486,544 @@ impl Arguments {
                 Ok(neighbor_algorithm) => neighbor_algorithm,
                 Err(_) => {
                     eprintln!(
-                        "Invalid algorithm.\nPlease choose from {}, or {}.\nAborting...",
+                        "Invalid algorithm.\nPlease choose from {}, {}, {}, or vn followed by a radius up to {MAX_RADIUS}.\nAborting...",
                         NeighborRule::Moore,
-                        NeighborRule::VonNeumann
+                        NeighborRule::VonNeumann,
+                        NeighborRule::Hex,
                     );
                     std::process::exit(exitcode::CONFIG);
                 }
             },
-            None => NeighborRule::Moore,
+            None => match cli.kernel {
+                Some(ref kernel) => match read_kernel(kernel) {
+                    Ok(neighbor_algorithm) => neighbor_algorithm,
+                    Err(e) => {
+                        eprintln!(
+                            "Could not read the kernel {}: {e}\nAborting...",
+                            kernel.display()
+                        );
+                        std::process::exit(exitcode::NOINPUT);
+                    }
+                },
+                None => NeighborRule::Moore,
+            },
         };
 
-        let state = cli.state.unwrap_or(1);
-
-        let rule = Rule::new(
-            LifeRule::Raw([false, false, true, true, false, false, false, false, false]),
-            LifeRule::Raw([false, false, false, true, false, false, false, false, false]),
-            state,
+        let border = match cli.border {
+            Some(ref border_string) => match BorderRule::from_str(border_string) {
+                Ok(border) => border,
+                Err(_) => {
+                    eprintln!(
+                        "Invalid border rule.\nPlease choose from {}, {}, {}, {}, or {}.\nAborting...",
+                        BorderRule::Constant,
+                        BorderRule::Nearest,
+                        BorderRule::Mirror,
+                        BorderRule::Reflect,
+                        BorderRule::Wrap,
+                    );
+                    std::process::exit(exitcode::CONFIG);
+                }
+            },
+            None => BorderRule::Constant,
+        };
+        // RLE patterns can declare the rule they are meant for, which applies unless a rule was given.
+        let rle_path = match cli.command {
+            Some(Commands::Rle2gif { ref input, .. }) => Some(input),
+            _ => cli.image.as_ref().filter(|image| {
+                image
+                    .extension()
+                    .is_some_and(|extension| extension == "rle")
+            }),
+        };
+        let rle_rule = rle_path.and_then(|path| read_rle_rule(path).ok().flatten());
+        let rule = match cli.rule {
+            Some(ref rule_string) => match Rule::from_str(rule_string) {
+                Ok(rule) => {
+                    if let Some(ref rle_rule) = rle_rule {
+                        if Rule::from_str(rle_rule).ok().as_ref() != Some(&rule) {
+                            log::warn!("the pattern declares the rule {rle_rule}, but the given rule {rule_string} takes precedence.");
+                        }
+                    }
+                    rule
+                }
+                Err(_) => {
+                    eprintln!(
+                        "Invalid rule.\nPlease use the B/S (e.g. B3/S23) or S/B/C (e.g. 23/3/8) notation.\nAborting..."
+                    );
+                    std::process::exit(exitcode::CONFIG);
+                }
+            },
+            None => match rle_rule {
+                Some(ref rle_rule) => Rule::from_str(rle_rule).unwrap_or_else(|_| {
+                    log::warn!("the pattern declares the unsupported rule {rle_rule}, using B3/S23 instead.");
+                    Rule::default()
+                }),
+                None => Rule::default(),
+            },
+        };
+        // Rulestrings are parsed for the Moore neighbor rule, so the counts are checked again against the chosen one.
+        let raw = |array: &[bool]| {
+            LifeRule::Raw(
+                array
+                    .try_into()
+                    .expect("rulestrings only have counts up to 8"),
+            )
+        };
+        let rule = match Rule::new(
+            raw(&rule.survival),
+            raw(&rule.birth),
+            cli.state.map_or(rule.state, u32::from),
             neighbor_algorithm,
-        );
+        ) {
+            Ok(rule) => rule,
+            Err(e) => {
+                eprintln!("{e}.\nAborting...");
+                std::process::exit(exitcode::CONFIG);
+            }
+        };
+        let state = match check_state::<u8>(rule.state) {
+            Ok(state) => state,
+            Err(e) => {
+                eprintln!("{e}.\nAborting...");
+                std::process::exit(exitcode::CONFIG);
+            }
+        };
 
-        let iterations = cli.iterations.unwrap_or(10);
+        let mut iterations = cli.iterations.unwrap_or(10);
         let time_per_iteration = Duration::from_millis(cli.timeiter.unwrap_or(500) as u64);
         let probability = cli.probability.unwrap_or(0.2);
         if !(0.0..=1.0).contains(&probability) {
             eprintln!("Probability has to between 0 and 1!\nAborting...");
             std::process::exit(exitcode::CONFIG);
         }
+        let symmetry = match cli.symmetry {
+            Some(ref symmetry_string) => match Symmetry::from_str(symmetry_string) {
+                Ok(symmetry) => symmetry,
+                Err(_) => {
+                    eprintln!(
+                        "Invalid symmetry.\nPlease choose from {}, {}, {}, {}, or {}.\nAborting...",
+                        Symmetry::None,
+                        Symmetry::Horizontal,
+                        Symmetry::Vertical,
+                        Symmetry::Both,
+                        Symmetry::Rotational,
+                    );
+                    std::process::exit(exitcode::CONFIG);
+                }
+            },
+            None => Symmetry::None,
+        };
+        let init_states = match cli.init_states {
+            Some(ref init_states_string) => match InitStates::from_str(init_states_string) {
+                Ok(init_states) => init_states,
+                Err(_) => {
+                    eprintln!(
+                        "Invalid initial states.\nPlease choose from {}, uniform, or {}.\nAborting...",
+                        InitStates::Max,
+                        InitStates::Random,
+                    );
+                    std::process::exit(exitcode::CONFIG);
+                }
+            },
+            None => InitStates::Max,
+        };
+
+        let scale = cli.scale.unwrap_or(1);
+        if scale == 0 {
+            eprintln!("The scale has to be at least 1.\nAborting...");
+            std::process::exit(exitcode::CONFIG);
+        }
+        let resolution = cli.resolution.as_ref().map(|resolution_string| {
+            match Resolution::from_str(resolution_string) {
+                Ok(resolution) => resolution,
+                Err(_) => {
+                    eprintln!(
+                        "Invalid resolution.\nPlease give it as WIDTHxHEIGHT, e.g. 1920x1080.\nAborting..."
+                    );
+                    std::process::exit(exitcode::CONFIG);
+                }
+            }
+        });
+        let aspect = match cli.aspect {
+            Some(ref aspect_string) => match Aspect::from_str(aspect_string) {
+                Ok(aspect) => aspect,
+                Err(_) => {
+                    eprintln!(
+                        "Invalid aspect ratio.\nPlease give it as WIDTH:HEIGHT, e.g. 1:2.\nAborting..."
+                    );
+                    std::process::exit(exitcode::CONFIG);
+                }
+            },
+            None => Aspect::default(),
+        };
+        #[cfg_attr(not(feature = "checkpoint"), allow(unused_mut))]
+        let mut margin = cli.margin.unwrap_or(0) as usize;
+        // Files fit the field into the resolution if one is given, including the margin.
+        let file_size = |cell_width: u32, cell_height: u32| match resolution {
+            Some(resolution) => {
+                let (numx, numy) = resolution.field_size(cell_width, cell_height);
+                let margin = (2 * margin).try_into().unwrap_or(u32::MAX);
+                (numx.saturating_sub(margin), numy.saturating_sub(margin))
+            }
+            None => (cli.x.unwrap_or(10), cli.y.unwrap_or(10)),
+        };
+
+        let mut summary = cli.summary.as_ref().map(|format_string| {
+            match SummaryFormat::from_str(format_string) {
+                Ok(format) => format,
+                Err(_) => {
+                    eprintln!(
+                        "Invalid summary format.\nPlease choose from {} or {}.\nAborting...",
+                        SummaryFormat::Text,
+                        SummaryFormat::Json,
+                    );
+                    std::process::exit(exitcode::CONFIG);
+                }
+            }
+        });
+        let noise = cli.noise.as_ref().map(|noise_string| {
+            match Noise::from_str(noise_string) {
+                Ok(noise) => noise,
+                Err(_) => {
+                    eprintln!(
+                        "Invalid noise.\nPlease give it as PROBABILITY:GENERATIONS with a probability between 0 and 1 and at least 1 generation, e.g. 0.01:10.\nAborting..."
+                    );
+                    std::process::exit(exitcode::CONFIG);
+                }
+            }
+        });
+        let threads = cli.threads.inspect(|&threads| {
+            if threads == 0 {
+                eprintln!("The number of threads has to be at least 1.\nAborting...");
+                std::process::exit(exitcode::CONFIG);
+            }
+        });
+
+        let max_size = cli.max_size.map(|megabytes| {
+            if megabytes <= 0.0 {
+                eprintln!("The maximum size has to be greater than 0.\nAborting...");
+                std::process::exit(exitcode::CONFIG);
+            }
+            (megabytes * 1_000_000.0) as u64
+        });
 
         let presentation: Presentations;
 
-        let numx: u32;
-        let numy: u32;
+        let mut numx: u32;
+        let mut numy: u32;
 
         let output_file: Option<PathBuf>;
 
         let progressbar: Option<ProgressBar>;
 
+        let mut rle_field = None;
+
         match cli.command.as_ref().unwrap() {
             Commands::Gif { ref output } => {
                 presentation = Presentations::Gif;
-                output_file = Some(handle_path(output).expect("path inquire"));
-                numx = cli.x.unwrap_or(10);
-                numy = cli.y.unwrap_or(10);
-                let pb_def = ProgressBar::new(iterations as u64);
-                pb_def.set_style(
-                    ProgressStyle::with_template(
-                        "{spinner:.green} [{elapsed}] [{wide_bar:.cyan/blue}] {pos}/{len} ({eta})",
-                    )
-                    .expect("progressbar")
-                    .progress_chars("#>-"),
-                );
-                progressbar = Some(pb_def);
+                output_file = Some(handle_path(output, GIF_EXTENSIONS).expect("path inquire"));
+                (numx, numy) = file_size(scale * aspect.width, scale * aspect.height);
+                progressbar = Some(create_progressbar(iterations));
+            }
+            Commands::Png { ref output } => {
+                presentation = Presentations::PngSequence;
+                output_file = Some(handle_directory(output));
+                (numx, numy) = file_size(scale, scale);
+                progressbar = Some(create_progressbar(iterations));
+            }
+            Commands::Snapshot { ref output, at } => {
+                if let Some(iterations) = cli.iterations.filter(|&iterations| *at > iterations) {
+                    eprintln!(
+                        "The snapshot at generation {at} is past the last one of {iterations} iterations.\nAborting..."
+                    );
+                    std::process::exit(exitcode::CONFIG);
+                }
+                presentation = Presentations::Snapshot;
+                output_file = Some(handle_path(output, PNG_EXTENSIONS).expect("path inquire"));
+                (numx, numy) = file_size(scale, scale);
+                iterations = *at;
+                progressbar = Some(create_progressbar(iterations));
+            }
+            Commands::Video { ref output } => {
+                presentation = Presentations::Video;
+                output_file = Some(handle_path(output, VIDEO_EXTENSIONS).expect("path inquire"));
+                (numx, numy) = file_size(scale, scale);
+                progressbar = Some(create_progressbar(iterations));
+            }
+            Commands::Rle2gif {
+                ref input,
+                ref output,
+                padding,
+            } => {
+                presentation = Presentations::Gif;
+                output_file = Some(handle_path(output, GIF_EXTENSIONS).expect("path inquire"));
+                let field = match read_rle(input, state) {
+                    Ok(field) => pad(&field, *padding as usize),
+                    Err(e) => {
+                        eprintln!(
+                            "Could not read the pattern {}: {e}\nAborting...",
+                            input.display()
+                        );
+                        std::process::exit(exitcode::NOINPUT);
+                    }
+                };
+                (numx, numy) = (field.nrows() as u32, field.ncols() as u32);
+                rle_field = Some(field);
+                progressbar = Some(create_progressbar(iterations));
             }
             Commands::Tui => {
                 presentation = Presentations::Tui;
                 output_file = None;
                 (numx, numy) = get_size(cli.x, cli.y);
                 progressbar = None;
+                if resolution.is_some() || cli.scale.is_some() {
+                    log::warn!(
+                        "the TUI shows one cell per character, ignoring --resolution and --scale."
+                    );
+                }
+            }
+            Commands::Rule { .. } => unreachable!("the rule command does not run a Game of Life"),
+            Commands::Bench => {
+                presentation = Presentations::Benchmark;
+                output_file = None;
+                (numx, numy) = (cli.x.unwrap_or(100), cli.y.unwrap_or(100));
+                progressbar = None;
+                if resolution.is_some() || cli.scale.is_some() {
+                    log::warn!("the benchmark draws nothing, ignoring --resolution and --scale.");
+                }
             }
         }
+        if max_size.is_some() && !matches!(presentation, Presentations::Gif) {
+            log::warn!("only GIFs have a maximum size, ignoring --max-size.");
+        }
+        if cli.hold_last.is_some() && !matches!(presentation, Presentations::Gif) {
+            log::warn!("only GIFs loop, ignoring --hold-last.");
+        }
+        if summary.is_some()
+            && matches!(presentation, Presentations::Tui | Presentations::Benchmark)
+        {
+            log::warn!(
+                "only GIFs, PNG sequences, videos, and snapshots have a summary, ignoring --summary."
+            );
+            summary = None;
+        }
+        if cli.history.is_some() && !matches!(presentation, Presentations::Tui) {
+            log::warn!("only the TUI keeps a history, ignoring --history.");
+        }
+        if cli.no_color && !matches!(presentation, Presentations::Tui) {
+            log::warn!("only the TUI can be drawn without colors, ignoring --no-color.");
+        }
+        if cli.aspect.is_some() && !matches!(presentation, Presentations::Gif) {
+            log::warn!("only GIFs have an aspect ratio, ignoring --aspect.");
+        }
+        // The snapshot of generation 0 is the initial field.
+        if iterations == 0 && !matches!(presentation, Presentations::Tui | Presentations::Snapshot)
+        {
+            eprintln!(
+                "Only the {} can run indefinitely, the {presentation} needs a finite number of iterations.\nAborting...",
+                Presentations::Tui,
+            );
+            std::process::exit(exitcode::CONFIG);
+        }
+
+        let mut initial_field = cli.image.as_ref().map(|image| {
+            let field = match image.extension().and_then(|extension| extension.to_str()) {
+                Some("cells") => read_plaintext(image, state),
+                Some("rle") => read_rle(image, state),
+                Some("lif" | "life") => read_life106(image, state),
+                _ => read_image(image, cli.threshold.unwrap_or(128), state).map_err(Into::into),
+            };
+            match field {
+                Ok(field) => field,
+                Err(e) => {
+                    eprintln!(
+                        "Could not read the image {}: {e}\nAborting...",
+                        image.display()
+                    );
+                    std::process::exit(exitcode::NOINPUT);
+                }
+            }
+        });
+        if let Some(ref field) = initial_field {
+            if cli.x.is_some() || cli.y.is_some() || resolution.is_some() {
+                log::warn!(
+                    "the size of the field is taken from the image, ignoring -x, -y, and --resolution."
+                );
+            }
+            numx = field.nrows() as u32;
+            numy = field.ncols() as u32;
+        }
+        if let Some(ref pattern_string) = cli.seed_pattern {
+            let pattern = match Pattern::from_str(pattern_string) {
+                Ok(pattern) => pattern,
+                Err(_) => {
+                    eprintln!(
+                        "Invalid pattern.\nPlease choose from {}, {}, {}, {}, or {}.\nAborting...",
+                        Pattern::Glider,
+                        Pattern::Blinker,
+                        Pattern::Lwss,
+                        Pattern::Pulsar,
+                        Pattern::GosperGliderGun,
+                    );
+                    std::process::exit(exitcode::CONFIG);
+                }
+            };
+            initial_field = Some(place_pattern(pattern, numx, numy, state));
+        }
+        if let Some(field) = rle_field {
+            if initial_field.is_some() || cli.x.is_some() || cli.y.is_some() || resolution.is_some()
+            {
+                log::warn!(
+                    "the field is taken from the RLE pattern, ignoring --image, --seed-pattern, -x, -y, and --resolution."
+                );
+            }
+            initial_field = Some(field);
+        }
+        if initial_field.is_some() && symmetry != Symmetry::None {
+            log::warn!("only random fields can be symmetric, ignoring --symmetry.");
+        }
+        if initial_field.is_some() && init_states != InitStates::Max {
+            log::warn!("only random fields have random states, ignoring --init-states.");
+        }
+        let density_map = match cli.density_map {
+            Some(_) if initial_field.is_some() => {
+                log::warn!("only random fields have a density, ignoring --density-map.");
+                None
+            }
+            Some(ref path) => match read_density_map(path) {
+                Ok(density_map) => Some(density_map),
+                Err(e) => {
+                    eprintln!(
+                        "Could not read the density map {}: {e}\nAborting...",
+                        path.display()
+                    );
+                    std::process::exit(exitcode::NOINPUT);
+                }
+            },
+            None => None,
+        };
+        #[cfg(feature = "checkpoint")]
+        let (algorithm, rule, border, generation, seed) = match cli.resume {
+            Some(ref path) => {
+                let checkpoint = match read_checkpoint(path) {
+                    Ok(checkpoint) => checkpoint,
+                    Err(e) => {
+                        eprintln!(
+                            "Could not read the checkpoint {}: {e}\nAborting...",
+                            path.display()
+                        );
+                        std::process::exit(exitcode::NOINPUT);
+                    }
+                };
+                let field = match checkpoint.field() {
+                    Ok(field) => field,
+                    Err(e) => {
+                        eprintln!("Invalid checkpoint {}: {e}\nAborting...", path.display());
+                        std::process::exit(exitcode::DATAERR);
+                    }
+                };
+                if initial_field.is_some()
+                    || cli.x.is_some()
+                    || cli.y.is_some()
+                    || resolution.is_some()
+                    || cli.rule.is_some()
+                    || cli.algorithm.is_some()
+                    || cli.border.is_some()
+                {
+                    log::warn!(
+                        "the field, rule, algorithm, and border rule are taken from the checkpoint, ignoring the flags that set them."
+                    );
+                }
+                if margin != 0 {
+                    log::warn!("the checkpoint already contains the margin, ignoring --margin.");
+                    margin = 0;
+                }
+                if cli.trail.is_some_and(|trail| trail > 0) {
+                    eprintln!("The trail is not saved in checkpoints, so a resumed run cannot continue it.\nAborting...");
+                    std::process::exit(exitcode::CONFIG);
+                }
+                (numx, numy) = (field.nrows() as u32, field.ncols() as u32);
+                initial_field = Some(field);
+                (
+                    checkpoint.algorithm,
+                    checkpoint.board.rule,
+                    checkpoint.border,
+                    checkpoint.generation,
+                    cli.seed.or(checkpoint.seed),
+                )
+            }
+            None => (algorithm, rule, border, 0, cli.seed),
+        };
+        #[cfg(not(feature = "checkpoint"))]
+        let seed = cli.seed;
+        // Without a seed, one is drawn for the noise, so that checkpoints can continue it.
+        let seed = seed.or_else(|| noise.map(|_| rand::random()));
+        #[cfg(feature = "checkpoint")]
+        let checkpoint = match cli.checkpoint {
+            Some(ref path) => {
+                let every = cli.checkpoint_every.unwrap_or(100);
+                if every == 0 {
+                    eprintln!("The number of generations between two checkpoints has to be at least 1.\nAborting...");
+                    std::process::exit(exitcode::CONFIG);
+                }
+                Some((path.clone(), every))
+            }
+            None => {
+                if cli.checkpoint_every.is_some() {
+                    log::warn!("no checkpoint file was given, ignoring --checkpoint-every.");
+                }
+                None
+            }
+        };
+        let dump_initial = cli
+            .dump_initial
+            .as_ref()
+            .map(|path| handle_path(path, PLAINTEXT_EXTENSIONS).expect("path inquire"));
+        let activity = match cli.activity {
+            Some(_) if matches!(presentation, Presentations::Benchmark) => {
+                log::warn!("the benchmark only times the generations, ignoring --activity.");
+                None
+            }
+            Some(ref path) => Some(handle_path(path, PNG_EXTENSIONS).expect("path inquire")),
+            None => None,
+        };
+
         Arguments {
             presentation,
             output_file,
             iterations,
             time_per_iteration,
+            stop_when_stable: cli.stop_when_stable,
+            history: cli.history.unwrap_or(100),
+            color: !cli.no_color,
+            summary,
+            activity,
+            #[cfg(feature = "checkpoint")]
+            checkpoint,
+            #[cfg(feature = "checkpoint")]
+            generation,
+            threads,
+            scale: scale as usize,
+            aspect,
+            max_size,
+            hold_last: cli
+                .hold_last
+                .map(|millis| Duration::from_millis(millis as u64)),
+            trail: cli.trail.unwrap_or(0),
+            noise,
             numx,
             numy,
             algorithm,
             rule,
+            border,
             probability,
+            seed,
+            density_map,
+            symmetry,
+            init_states,
+            initial_field,
+            margin,
+            dump_initial,
             progressbar,
         }
     }
@@ -211,41 +1032,83 @@ impl Arguments {
     fn from_dialogue() -> Result<Self, InquireError> {
         let presentation = Select::new(
             "How do you want to present the Game of Life?",
-            vec![Presentations::Gif, Presentations::Tui],
+            vec![
+                Presentations::Gif,
+                Presentations::PngSequence,
+                Presentations::Video,
+                Presentations::Tui,
+            ],
         )
         .with_vim_mode(true)
         .prompt()?;
         let output_file = match presentation {
             Presentations::Gif => {
                 let file_answer = Text::new("Where should the GIF be saved?")
-                    .with_validators(&[Box::new(file_validator), Box::new(required!())])
-                    .with_formatter(&format_path)
+                    .with_validators(&[
+                        Box::new(|text: &str| file_validator(text, GIF_EXTENSIONS)),
+                        Box::new(required!()),
+                    ])
+                    .with_formatter(&|text| format_path(text, GIF_EXTENSIONS))
+                    .prompt()?;
+                Some(handle_path(file_answer, GIF_EXTENSIONS).expect("path inquire"))
+            }
+            Presentations::Video => {
+                let file_answer = Text::new("Where should the video be saved?")
+                    .with_validators(&[
+                        Box::new(|text: &str| file_validator(text, VIDEO_EXTENSIONS)),
+                        Box::new(required!()),
+                    ])
+                    .with_formatter(&|text| format_path(text, VIDEO_EXTENSIONS))
+                    .prompt()?;
+                Some(handle_path(file_answer, VIDEO_EXTENSIONS).expect("path inquire"))
+            }
+            Presentations::PngSequence => {
+                let directory_answer = Text::new("In which directory should the PNGs be saved?")
+                    .with_validator(required!())
                     .prompt()?;
-                Some(handle_path(file_answer).expect("path inquire"))
+                Some(handle_directory(directory_answer))
             }
-            Presentations::Tui => None,
+            Presentations::Tui | Presentations::Benchmark | Presentations::Snapshot => None,
         };
 
-        let algorithm = Select::new(
-            "Which algorithm do you want to use?",
-            vec![Algorithm::Std, Algorithm::Conv],
-        )
-        .with_vim_mode(true)
-        .with_starting_cursor(1)
-        .prompt()?;
-
-        let iterations = CustomType::<usize>::new("How many iterations do you want to see?")
-            .with_default(10)
-            .with_validator(|i: &usize| {
-                if *i == 0 {
-                    return Ok(Validation::Invalid(
-                        "Iteration number has to be greater than 0".into(),
-                    ));
-                }
-                Ok(Validation::Valid)
-            })
+        let algorithm = Select::new("Which algorithm do you want to use?", Algorithm::all())
+            .with_vim_mode(true)
+            .with_starting_cursor(1)
             .prompt()?;
 
+        let border = match algorithm {
+            Algorithm::Conv => Select::new(
+                "How should the field be extended beyond its borders?",
+                vec![
+                    BorderRule::Constant,
+                    BorderRule::Nearest,
+                    BorderRule::Mirror,
+                    BorderRule::Reflect,
+                    BorderRule::Wrap,
+                ],
+            )
+            .with_vim_mode(true)
+            .prompt()?,
+            _ => BorderRule::Constant,
+        };
+
+        let unbounded = matches!(presentation, Presentations::Tui);
+        let iterations = CustomType::<usize>::new(if unbounded {
+            "How many iterations do you want to see (0 runs until you quit)?"
+        } else {
+            "How many iterations do you want to see?"
+        })
+        .with_default(10)
+        .with_validator(move |i: &usize| {
+            if *i == 0 && !unbounded {
+                return Ok(Validation::Invalid(
+                    "Iteration number has to be greater than 0".into(),
+                ));
+            }
+            Ok(Validation::Valid)
+        })
+        .prompt()?;
+
         let time_answer =
             CustomType::<u64>::new("How much time should every iteration take (in ms)?")
                 .with_default(500)
@@ -253,7 +1116,11 @@ impl Arguments {
         let time_per_iteration = Duration::from_millis(time_answer);
 
         let (numx, numy) = match presentation {
-            Presentations::Gif => (
+            Presentations::Gif
+            | Presentations::PngSequence
+            | Presentations::Video
+            | Presentations::Benchmark
+            | Presentations::Snapshot => (
                 CustomType::<u32>::new("How many columns should the field have?")
                     .with_default(10)
                     .with_validator(|i: &u32| {
@@ -298,33 +1165,72 @@ impl Arguments {
             }
         };
 
-        let probability = CustomType::<f32>::new(
-            "With which probability should each cell of the initial field be alive?",
+        let scale = match presentation {
+            Presentations::Tui => 1,
+            _ => CustomType::<usize>::new("How many pixels wide and high should every cell be?")
+                .with_default(1)
+                .with_validator(|i: &usize| {
+                    if *i == 0 {
+                        return Ok(Validation::Invalid("Has to be greater than 0".into()));
+                    }
+                    Ok(Validation::Valid)
+                })
+                .prompt()?,
+        };
+
+        let initialization = Select::new(
+            "How should the initial field look like?",
+            vec![
+                Initialization::Random,
+                Initialization::Pattern(Pattern::Glider),
+                Initialization::Pattern(Pattern::Blinker),
+                Initialization::Pattern(Pattern::Lwss),
+                Initialization::Pattern(Pattern::Pulsar),
+                Initialization::Pattern(Pattern::GosperGliderGun),
+            ],
         )
-        .with_default(0.2)
-        .with_validator(|p: &f32| {
-            if (0.0..=1.0).contains(p) {
-                return Ok(Validation::Valid);
-            }
-            Ok(Validation::Invalid(
-                "Probability has to be between 0 and 1".into(),
-            ))
-        })
+        .with_vim_mode(true)
         .prompt()?;
 
+        let probability = match initialization {
+            Initialization::Random => CustomType::<f32>::new(
+                "With which probability should each cell of the initial field be alive?",
+            )
+            .with_default(0.2)
+            .with_validator(|p: &f32| {
+                if (0.0..=1.0).contains(p) {
+                    return Ok(Validation::Valid);
+                }
+                Ok(Validation::Invalid(
+                    "Probability has to be between 0 and 1".into(),
+                ))
+            })
+            .prompt()?,
+            Initialization::Pattern(_) => 0.0,
+        };
+
+        let symmetry = match initialization {
+            Initialization::Random => Select::new(
+                "Should the initial field be symmetric?",
+                vec![
+                    Symmetry::None,
+                    Symmetry::Horizontal,
+                    Symmetry::Vertical,
+                    Symmetry::Both,
+                    Symmetry::Rotational,
+                ],
+            )
+            .with_vim_mode(true)
+            .prompt()?,
+            Initialization::Pattern(_) => Symmetry::None,
+        };
+
         let progressbar = match presentation {
-            Presentations::Gif => {
-                let pb_def = ProgressBar::new(iterations as u64);
-                pb_def.set_style(
-                    ProgressStyle::with_template(
-                        "{spinner:.green} [{elapsed}] [{wide_bar:.cyan/blue}] {pos}/{len} ({eta})",
-                    )
-                    .expect("progressbar")
-                    .progress_chars("#>-"),
-                );
-                Some(pb_def)
-            }
-            Presentations::Tui => None,
+            Presentations::Gif
+            | Presentations::PngSequence
+            | Presentations::Video
+            | Presentations::Snapshot => Some(create_progressbar(iterations)),
+            Presentations::Tui | Presentations::Benchmark => None,
         };
 
         let rule = if !Confirm::new("Do you want to change the default rules?")
@@ -335,34 +1241,49 @@ impl Arguments {
         } else {
             let neighbor = Select::new(
                 "Which neighbor rule do you want to use?",
-                vec![NeighborRule::Moore, NeighborRule::VonNeumann],
+                vec![
+                    NeighborRule::Moore,
+                    NeighborRule::VonNeumann,
+                    NeighborRule::Hex,
+                ],
             )
             .prompt()?;
 
             let survival = MultiSelect::new(
                 "With what amount of neighbors should a cell survive?",
-                (0..=8).collect::<Vec<usize>>(),
+                (0..=neighbor.max_neighbors()).collect::<Vec<usize>>(),
             )
             .prompt()?;
             let survival = LifeRule::Numbers(&survival);
 
             let birth = MultiSelect::new(
                 "With what amount of neighbors should a cell be born?",
-                (0..=8).collect::<Vec<usize>>(),
+                (0..=neighbor.max_neighbors()).collect::<Vec<usize>>(),
             )
             .prompt()?;
             let birth = LifeRule::Numbers(&birth);
 
             let state = CustomType::<u8>::new("How many iterations should a cell take to die?")
                 .with_validator(|i: &u8| {
-                    if *i == 0 {
-                        return Ok(Validation::Invalid("Has to be greater than 0".into()));
+                    if *i == 0 || *i == WALL {
+                        return Ok(Validation::Invalid(
+                            format!("Has to be between 1 and {}", WALL - 1).into(),
+                        ));
                     }
                     Ok(Validation::Valid)
                 })
                 .prompt()?;
 
-            Rule::new(survival, birth, state, neighbor)
+            // Only counts the neighbor rule can reach were offered.
+            Rule::new(survival, birth, state.into(), neighbor).unwrap()
+        };
+
+        let initial_field = match initialization {
+            Initialization::Random => None,
+            Initialization::Pattern(pattern) => {
+                // The dialogue only offers states below the wall.
+                Some(place_pattern(pattern, numx, numy, rule.state as u8))
+            }
         };
 
         Ok(Arguments {
@@ -370,31 +1291,79 @@ impl Arguments {
             output_file,
             iterations,
             time_per_iteration,
+            stop_when_stable: false,
+            history: 100,
+            color: true,
+            summary: None,
+            activity: None,
+            #[cfg(feature = "checkpoint")]
+            checkpoint: None,
+            #[cfg(feature = "checkpoint")]
+            generation: 0,
+            threads: None,
+            scale,
+            aspect: Aspect::default(),
+            max_size: None,
+            hold_last: None,
+            trail: 0,
+            noise: None,
             numx,
             numy,
             algorithm,
             rule,
+            border,
             probability,
+            seed: None,
+            density_map: None,
+            symmetry,
+            init_states: InitStates::Max,
+            initial_field,
+            margin: 0,
+            dump_initial: None,
             progressbar,
         })
     }
 }
 
+/// Extensions of GIF files
+const GIF_EXTENSIONS: &[&str] = &["gif"];
+/// Extensions of video files, the first one is the default
+const VIDEO_EXTENSIONS: &[&str] = &["mp4", "webm", "mkv"];
+/// Extensions of plaintext pattern files
+const PLAINTEXT_EXTENSIONS: &[&str] = &["cells"];
+/// Extensions of the heat map of `--activity` and of snapshots
+const PNG_EXTENSIONS: &[&str] = &["png"];
+
+/// Lists the extensions for error messages, e.g. `".mp4", ".webm", or ".mkv"`.
+fn describe_extensions(extensions: &[&str]) -> String {
+    let quoted: Vec<String> = extensions.iter().map(|ext| format!("\".{ext}\"")).collect();
+    match quoted.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{}, or {last}", rest.join(", ")),
+        _ => quoted.join(""),
+    }
+}
+
 /// Handles the path to the output file.
 ///
 /// If the file exists, the user is prompted whether to overwrite it. If not, the program terminate.
-/// If the file name has a different extension than ".gif", the program terminates with an error message. If the file name has no extension, ".gif" is appended.
-fn handle_path<P: AsRef<Path>>(output_path: P) -> Result<PathBuf, InquireError> {
+/// If the file name has an extension not in `extensions`, the program terminates with an error message. If the file name has no extension, the first one is appended.
+fn handle_path<P: AsRef<Path>>(
+    output_path: P,
+    extensions: &[&str],
+) -> Result<PathBuf, InquireError> {
     let mut output_path = output_path.as_ref().to_path_buf();
     match output_path.extension() {
         Some(extension) => {
-            if extension != "gif" {
-                eprintln!("The field must be saved as a \".gif\" file.\nAborting...");
+            if !extensions.iter().any(|ext| extension == *ext) {
+                eprintln!(
+                    "The field must be saved as a {} file.\nAborting...",
+                    describe_extensions(extensions)
+                );
                 std::process::exit(exitcode::CONFIG);
             };
         }
         None => {
-            output_path.set_extension("gif");
+            output_path.set_extension(extensions[0]);
         }
     }
     if output_path.exists() {
@@ -417,9 +1386,65 @@ fn handle_path<P: AsRef<Path>>(output_path: P) -> Result<PathBuf, InquireError>
     Ok(output_path)
 }
 
+/// Handles the path to the output directory.
+///
+/// The directory is created if it does not exist. If it is not a writable directory, the program terminates with an error message.
+fn handle_directory<P: AsRef<Path>>(output_path: P) -> PathBuf {
+    let output_path = output_path.as_ref().to_path_buf();
+    if let Err(e) = std::fs::create_dir_all(&output_path) {
+        eprintln!(
+            "Could not create the directory {}: {e}\nAborting...",
+            output_path.display()
+        );
+        std::process::exit(exitcode::CANTCREAT);
+    }
+    let writable = std::fs::metadata(&output_path)
+        .map(|metadata| metadata.is_dir() && !metadata.permissions().readonly())
+        .unwrap_or(false);
+    if !writable {
+        eprintln!(
+            "The directory {} is not writable.\nAborting...",
+            output_path.display()
+        );
+        std::process::exit(exitcode::CANTCREAT);
+    }
+
+    output_path
+}
+
+/// Places the pattern in the center of a field of size (numx, numy).
+///
+/// If the pattern does not fit into the field, the program terminates with an error message.
+fn place_pattern(pattern: Pattern, numx: u32, numy: u32, state: u8) -> Array2<u8> {
+    match pattern.place(numx as usize, numy as usize, state) {
+        Some(field) => field,
+        None => {
+            let (width, height) = pattern.size();
+            eprintln!(
+                "The {pattern} needs a field of at least {width}x{height} cells.\nAborting..."
+            );
+            std::process::exit(exitcode::CONFIG);
+        }
+    }
+}
+
+/// Creates the progress bar shown while saving files.
+fn create_progressbar(iterations: usize) -> ProgressBar {
+    let pb = ProgressBar::new(iterations as u64);
+    pb.set_style(
+        ProgressStyle::with_template(
+            "{spinner:.green} [{elapsed}] [{wide_bar:.cyan/blue}] {pos}/{len} ({eta}) {msg}",
+        )
+        .expect("progressbar")
+        .progress_chars("#>-"),
+    );
+    pb
+}
+
 /// `inquire` validator for filename input
 fn file_validator(
     text: &str,
+    extensions: &[&str],
 ) -> Result<Validation, Box<dyn std::error::Error + Send + Sync + 'static>> {
     let output_file = PathBuf::from_str(text);
     let output_file = match output_file {
@@ -428,103 +1453,462 @@ fn file_validator(
     };
     match output_file.extension() {
         Some(extension) => {
-            if extension == "gif" {
+            if extensions.iter().any(|ext| extension == *ext) {
                 return Ok(Validation::Valid);
             }
             Ok(Validation::Invalid(
-                "Field must be saved as a \".gif\" file".into(),
+                format!(
+                    "Field must be saved as a {} file",
+                    describe_extensions(extensions)
+                )
+                .into(),
             ))
         }
         None => Ok(Validation::Valid),
     }
 }
 
-/// Formats the path for `inquire`
-fn format_path(text: &str) -> String {
+/// Formats the path for `inquire`, appending the first extension if there is none
+fn format_path(text: &str, extensions: &[&str]) -> String {
     let mut output_file = PathBuf::from_str(text).unwrap();
-    output_file.set_extension("gif");
+    if output_file.extension().is_none() {
+        output_file.set_extension(extensions[0]);
+    }
     output_file.to_str().unwrap().to_owned()
 }
 
-/// Start the Game of Life
-fn start<G: GameOfLife>(
-    presentation: Presentations,
+/// Start the Game of Life, calling every hook after every computed generation
+fn start<G: GameOfLife<Data = u8> + Clone + 'static>(
     gol: G,
-    iterations: usize,
-    time_per_iteration: Duration,
-    pb: Option<ProgressBar>,
-    output_file: Option<PathBuf>,
+    arguments: Arguments,
+    mut hooks: Vec<OnGeneration<G>>,
 ) {
+    let Arguments {
+        presentation,
+        output_file,
+        iterations,
+        time_per_iteration,
+        stop_when_stable,
+        history,
+        color,
+        scale,
+        aspect,
+        max_size,
+        hold_last,
+        trail,
+        algorithm,
+        progressbar: pb,
+        ..
+    } = arguments;
+    let on_generation = move |generation, gol: &G| {
+        for hook in &mut hooks {
+            hook(generation, gol);
+        }
+    };
     match presentation {
         Presentations::Gif => {
+            let mut gif = GIF::new(gol)
+                .with_on_generation(on_generation)
+                .with_scale(scale)
+                .with_aspect(aspect)
+                .with_trail(trail);
+            if let Err(e) = gif.frame_size() {
+                eprintln!("{e}.\nAborting...");
+                std::process::exit(exitcode::CONFIG);
+            }
             let file = File::create(output_file.as_ref().unwrap()).unwrap();
-            let mut gif = GIF::new(gol);
-            gif.start(&file, iterations, time_per_iteration, pb)
-                .expect("running GIF presentation");
-            println!("Saved Game of Life to {}.", output_file.unwrap().display());
+            if let Some(max_size) = max_size {
+                gif = gif.with_max_size(max_size);
+            }
+            if let Some(hold_last) = hold_last {
+                gif = gif.with_hold_last(hold_last);
+            }
+            let frames =
+                match gif.start(&file, iterations, time_per_iteration, stop_when_stable, pb) {
+                    Ok(frames) => frames,
+                    Err(e) => {
+                        eprintln!(
+                            "{e}.\nThe frames up to then were saved to {}.\nAborting...",
+                            output_file.unwrap().display()
+                        );
+                        std::process::exit(match e {
+                            GolError::Panicked(_) => exitcode::SOFTWARE,
+                            _ => exitcode::IOERR,
+                        });
+                    }
+                };
+            let size = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+            if frames < iterations + 1 && max_size.is_some_and(|max_size| size > max_size) {
+                log::warn!(
+                    "the GIF exceeded the maximum size, so only {frames} of {} generations were saved.",
+                    iterations + 1
+                );
+            }
+            log::info!("Saved Game of Life to {}.", output_file.unwrap().display());
+        }
+        Presentations::PngSequence => {
+            let directory = output_file.unwrap();
+            let mut png = PngSequence::new(gol)
+                .with_on_generation(on_generation)
+                .with_scale(scale)
+                .with_trail(trail);
+            png.start(&directory, iterations, stop_when_stable, pb)
+                .expect("running PNG presentation");
+            log::info!("Saved Game of Life to {}.", directory.display());
+        }
+        Presentations::Snapshot => {
+            let output_file = output_file.unwrap();
+            let mut snapshot = Snapshot::new(gol)
+                .with_on_generation(on_generation)
+                .with_scale(scale)
+                .with_trail(trail);
+            if let Err(e) = snapshot.start(&output_file, iterations, stop_when_stable, pb) {
+                eprintln!(
+                    "Could not save the snapshot to {}: {e}\nAborting...",
+                    output_file.display()
+                );
+                std::process::exit(exitcode::CANTCREAT);
+            }
+            log::info!(
+                "Saved generation {iterations} to {}.",
+                output_file.display()
+            );
+        }
+        Presentations::Video => {
+            let output_file = output_file.unwrap();
+            let mut video = Video::new(gol)
+                .with_on_generation(on_generation)
+                .with_scale(scale)
+                .with_trail(trail);
+            match video.start(
+                &output_file,
+                iterations,
+                time_per_iteration,
+                stop_when_stable,
+                pb,
+            ) {
+                Ok(()) => log::info!("Saved Game of Life to {}.", output_file.display()),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    eprintln!("Encoding videos requires ffmpeg, which was not found on the PATH.\nAborting...");
+                    std::process::exit(exitcode::UNAVAILABLE);
+                }
+                Err(e) => {
+                    eprintln!("Could not encode the video: {e}\nAborting...");
+                    std::process::exit(exitcode::SOFTWARE);
+                }
+            }
         }
         Presentations::Tui => {
-            let mut tui = TUI::new(gol);
-            tui.start(iterations, time_per_iteration)
+            let mut tui = TUI::new(gol)
+                .with_on_generation(on_generation)
+                .with_history(history)
+                .with_trail(trail)
+                .with_color(color);
+            let iterations = (iterations != 0).then_some(iterations);
+            tui.start(iterations, time_per_iteration, stop_when_stable)
                 .expect("running TUI presentation");
         }
+        Presentations::Benchmark => println!(
+            "{}",
+            Benchmark::run(gol, iterations, algorithm, Some(Box::new(on_generation)))
+        ),
     }
 }
 
+/// Prints the log to stderr, only errors if `quiet` and also the timing of every generation if `verbose`.
+/// `RUST_LOG` takes precedence over both.
+fn init_logger(quiet: bool, verbose: bool) {
+    let level = if quiet {
+        LevelFilter::Error
+    } else if verbose {
+        LevelFilter::Debug
+    } else {
+        LevelFilter::Info
+    };
+    env_logger::Builder::new()
+        // Dependencies like wgpu are only heard from if something goes wrong.
+        .filter_level(level.min(LevelFilter::Warn))
+        .filter_module("game_of_life", level)
+        .format(|buf, record| match record.level() {
+            Level::Warn => writeln!(buf, "Warning: {}", record.args()),
+            _ => writeln!(buf, "{}", record.args()),
+        })
+        .parse_default_env()
+        .init();
+}
+
 fn main() {
     let cli = Cli::parse();
-    let arguments = match cli.command {
+    init_logger(cli.quiet, cli.verbose);
+    #[cfg(feature = "config")]
+    let cli = cli.with_config();
+    if let Some(Commands::Rule { ref rulestring }) = cli.command {
+        return describe_rule(rulestring);
+    }
+    let mut arguments = match cli.command {
         Some(_) => Arguments::parse_cli(&cli),
         None => match Arguments::from_dialogue() {
             Ok(arguments) => arguments,
             Err(InquireError::OperationInterrupted) => {
-                println!("Exiting...");
+                log::info!("Exiting...");
                 std::process::exit(130);
             }
             Err(InquireError::OperationCanceled) => {
-                println!("Exiting...");
+                log::info!("Exiting...");
                 std::process::exit(exitcode::OK);
             }
             Err(e) => panic!("{e}"),
         },
     };
+    if cli.quiet {
+        arguments.progressbar = None;
+    }
 
-    // Generate a random initial distribution
-    let mut rng = rand::thread_rng();
-    let field_vec: Vec<u8> = (0..arguments.numx * arguments.numy)
-        .map(|_| rng.gen_bool(arguments.probability as f64) as u8 * arguments.rule.state)
-        .collect();
+    // Generate a random initial distribution unless a field was loaded
+    let builder = GameBuilder::new()
+        .dimensions(arguments.numx as usize, arguments.numy as usize)
+        .rule(arguments.rule.clone())
+        .algorithm(arguments.algorithm)
+        .border(arguments.border)
+        .margin(arguments.margin);
+    let builder = match arguments.initial_field.take() {
+        Some(field) => builder.pattern(field),
+        None => {
+            let builder = builder
+                .random(arguments.probability as f64, arguments.seed)
+                .symmetry(arguments.symmetry)
+                .init_states(arguments.init_states);
+            match arguments.density_map.take() {
+                Some(density_map) => builder.density_map(density_map),
+                None => builder,
+            }
+        }
+    };
 
     // Pass the field to a GameOfLife instance and start it
-    match arguments.algorithm {
-        Algorithm::Std => {
-            let field_vec_std = field_vec.iter().map(|elem| AtomicU8::new(*elem)).collect();
-            let field = Array1::<AtomicU8>::from_vec(field_vec_std)
-                .into_shape((arguments.numx as usize, arguments.numy as usize))
-                .expect("field reshape");
-            let gol = GameOfLifeStd::new(field, arguments.rule);
-            start(
-                arguments.presentation,
-                gol,
-                arguments.iterations,
-                arguments.time_per_iteration,
-                arguments.progressbar,
-                arguments.output_file,
-            );
+    let gol = match builder.build() {
+        Ok(gol) => gol,
+        Err(e) => {
+            eprintln!("{e}.\nAborting...");
+            std::process::exit(match e {
+                BuildError::UnsupportedBorder(..) => exitcode::CONFIG,
+                BuildError::UnsupportedKernel(_) => exitcode::CONFIG,
+                BuildError::InvalidDimensions(_) => exitcode::CONFIG,
+                BuildError::InvalidState(_) => exitcode::CONFIG,
+                BuildError::InvalidNeighbor(_) => exitcode::CONFIG,
+                BuildError::InvalidProbability(_) => exitcode::CONFIG,
+                BuildError::InvalidDensityMap => exitcode::DATAERR,
+                #[cfg(feature = "gpu")]
+                BuildError::NoGpu => exitcode::UNAVAILABLE,
+            });
         }
-        Algorithm::Conv => {
-            let field = Array1::<u8>::from_vec(field_vec)
-                .into_shape((arguments.numx as usize, arguments.numy as usize))
-                .expect("field reshape");
-            let gol = GameOfLifeConvolution::new(field, arguments.rule);
-            start(
-                arguments.presentation,
-                gol,
-                arguments.iterations,
-                arguments.time_per_iteration,
-                arguments.progressbar,
-                arguments.output_file,
+    };
+
+    // Save the initial field before the simulation changes it
+    if let Some(ref path) = arguments.dump_initial {
+        let field = gol.snapshot();
+        if field.iter().any(|&cell| cell != 0 && cell != gol.state()) {
+            eprintln!("The plaintext format only keeps living and dead cells, so --dump-initial cannot save a field with decaying cells or walls, e.g. from --init-states random.\nAborting...");
+            std::process::exit(exitcode::CONFIG);
+        }
+        if let Err(e) = write_plaintext(path, &field, gol.state()) {
+            eprintln!(
+                "Could not save the initial field to {}: {e}\nAborting...",
+                path.display()
             );
+            std::process::exit(exitcode::CANTCREAT);
         }
     }
+
+    // Run the generations on a pool of the given size instead of rayon's global one
+    match arguments.threads {
+        Some(threads) => {
+            let pool = match rayon::ThreadPoolBuilder::new().num_threads(threads).build() {
+                Ok(pool) => pool,
+                Err(e) => {
+                    eprintln!("Could not start {threads} threads: {e}\nAborting...");
+                    std::process::exit(exitcode::OSERR);
+                }
+            };
+            pool.install(|| run(gol, arguments));
+        }
+        None => run(gol, arguments),
+    }
+}
+
+/// Prints the canonical form, the name, and the arrays of a rulestring, or exits if it is invalid.
+fn describe_rule(rulestring: &str) {
+    let Ok(rule) = Rule::from_str(rulestring) else {
+        eprintln!(
+            "Invalid rule {rulestring}.\nPlease use the B/S (e.g. B3/S23) or S/B/C (e.g. 23/3/8) notation.\nAborting..."
+        );
+        std::process::exit(exitcode::DATAERR);
+    };
+    let array = |array: &[bool]| {
+        array
+            .iter()
+            .map(|&set| if set { "1" } else { "0" })
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    println!("Rule: {rule}");
+    println!("Name: {}", rule.name().unwrap_or("unknown"));
+    println!("States: {}", rule.state as u64 + 1);
+    println!(
+        "Survival (0-{} neighbors): {}",
+        rule.survival.len() - 1,
+        array(&rule.survival)
+    );
+    println!(
+        "Birth (0-{} neighbors): {}",
+        rule.birth.len() - 1,
+        array(&rule.birth)
+    );
+}
+
+/// Starts the Game of Life with noise injected if it was requested.
+fn run(gol: Game, arguments: Arguments) {
+    match arguments.noise {
+        Some(noise) => {
+            #[cfg(feature = "checkpoint")]
+            let generation = arguments.generation;
+            #[cfg(not(feature = "checkpoint"))]
+            let generation = 0;
+            let seed = arguments.seed.unwrap_or_else(rand::random);
+            save(
+                Noisy::new(gol, noise, seed).with_generation(generation),
+                arguments,
+            )
+        }
+        None => save(gol, arguments),
+    }
+}
+
+/// Starts the Game of Life and saves checkpoints if a file was given.
+fn save<G: GameOfLife<Data = u8> + Clone + 'static>(gol: G, arguments: Arguments) {
+    #[cfg(feature = "checkpoint")]
+    if let Some((ref path, every)) = arguments.checkpoint {
+        let hook = Checkpointer::new(
+            path.clone(),
+            every,
+            arguments.generation,
+            arguments.algorithm,
+            arguments.border,
+            arguments.seed,
+        )
+        .hook();
+        return summarize(gol, arguments, vec![hook]);
+    }
+    summarize(gol, arguments, Vec::new())
+}
+
+/// Starts the Game of Life and prints the summary afterwards if one was requested.
+fn summarize<G: GameOfLife<Data = u8> + Clone + 'static>(
+    gol: G,
+    arguments: Arguments,
+    mut hooks: Vec<OnGeneration<G>>,
+) {
+    let Some(format) = arguments.summary else {
+        return record(gol, arguments, hooks);
+    };
+    let summary = Rc::<RefCell<Summary>>::default();
+    hooks.push(Summary::hook(&summary, &gol));
+    let begin = Instant::now();
+    record(gol, arguments, hooks);
+
+    let mut summary = summary.borrow_mut();
+    summary.elapsed = begin.elapsed();
+    println!("{}", summary.format(format));
+}
+
+/// Starts the Game of Life and saves the heat map of its activity afterwards if a file was given.
+fn record<G: GameOfLife<Data = u8> + Clone + 'static>(
+    gol: G,
+    mut arguments: Arguments,
+    mut hooks: Vec<OnGeneration<G>>,
+) {
+    let Some(path) = arguments.activity.take() else {
+        return start(gol, arguments, hooks);
+    };
+    let scale = arguments.scale;
+    let activity = Rc::default();
+    hooks.push(Activity::hook(&activity, &gol));
+    start(gol, arguments, hooks);
+
+    if let Err(e) = activity.borrow().write_png(&path, scale) {
+        eprintln!(
+            "Could not save the activity to {}: {e}\nAborting...",
+            path.display()
+        );
+        std::process::exit(exitcode::CANTCREAT);
+    }
+    log::info!("Saved the activity to {}.", path.display());
+}
+
+#[cfg(all(test, feature = "config"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn config_fills_missing_flags() {
+        let config: Config = toml::from_str(
+            r#"
+            iterations = 0
+            timeiter = 100
+            x = 30
+            y = 20
+            algorithm = "std"
+            neighbor = "vn"
+            probability = 0.5
+            symmetry = "both"
+            init-states = "random"
+            rule = "B3/S12"
+            "#,
+        )
+        .unwrap();
+        let mut cli = Cli::parse_from(["game-of-life", "-x", "40", "-s", "3", "tui"]);
+        cli.merge(config);
+
+        let arguments = Arguments::parse_cli(&cli);
+        assert_eq!(arguments.iterations, 0);
+        assert_eq!(arguments.time_per_iteration, Duration::from_millis(100));
+        assert_eq!((arguments.numx, arguments.numy), (40, 20));
+        assert_eq!(arguments.algorithm, Algorithm::Std);
+        assert_eq!(arguments.probability, 0.5);
+        assert_eq!(arguments.symmetry, Symmetry::Both);
+        assert_eq!(arguments.init_states, InitStates::Random);
+
+        let rule = Rule::new(
+            LifeRule::Range(1..3),
+            LifeRule::One(3),
+            3,
+            NeighborRule::VonNeumann,
+        )
+        .unwrap();
+        assert_eq!(arguments.rule, rule);
+
+        assert!(toml::from_str::<Config>("size = 10").is_err());
+    }
+
+    #[test]
+    fn kernel_sizes_rule() {
+        // The weights of the kernel sum up to 12, so the rule needs room for counts up to 12.
+        let kernel = std::env::temp_dir().join(format!("kernel-{}.txt", std::process::id()));
+        std::fs::write(&kernel, "1 1 1\n1 0 1\n1 1 5\n").unwrap();
+        let cli = Cli::parse_from([
+            "game-of-life",
+            "--kernel",
+            kernel.to_str().unwrap(),
+            "-r",
+            "B3/S23",
+            "bench",
+        ]);
+        let arguments = Arguments::parse_cli(&cli);
+        std::fs::remove_file(kernel).unwrap();
+
+        assert_eq!(arguments.rule.max_count(), 12);
+        assert_eq!(arguments.rule.survival.len(), 13);
+        assert_eq!(arguments.rule.birth.len(), 13);
+        assert!(arguments.rule.is_born(3) && !arguments.rule.is_born(12));
+    }
 }
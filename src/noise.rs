@@ -0,0 +1,170 @@
+//! # Noise
+//! Contains the random noise injected into a running field with `--noise`.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use game_of_life::gameoflife::*;
+use ndarray::Array2;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Flips every cell with `probability` after every `every` generations.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Noise {
+    pub probability: f64,
+    pub every: usize,
+}
+
+impl FromStr for Noise {
+    type Err = ();
+
+    /// Parses the noise as PROBABILITY:GENERATIONS, e.g. `0.01:10`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (probability, every) = s.split_once(':').ok_or(())?;
+        let probability: f64 = probability.trim().parse().map_err(|_| ())?;
+        let every: usize = every.trim().parse().map_err(|_| ())?;
+        if !(0.0..=1.0).contains(&probability) || every == 0 {
+            return Err(());
+        }
+        Ok(Self { probability, every })
+    }
+}
+
+impl Display for Noise {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.probability, self.every)
+    }
+}
+
+/// Wraps a Game of Life to inject [`Noise`] between the generations a presentation computes.
+///
+/// Unlike the checkpoints, the summary, and the activity, this is no `on_generation` hook,
+/// because hooks only borrow the game immutably and cannot flip its cells before the next frame.
+#[derive(Clone)]
+pub struct Noisy<G: GameOfLife> {
+    gameoflife: G,
+    noise: Noise,
+    generation: usize,
+    seed: u64,
+}
+
+impl<G: GameOfLife> Noisy<G> {
+    /// The same seed always produces the same noise.
+    pub fn new(gameoflife: G, noise: Noise, seed: u64) -> Self {
+        Self {
+            gameoflife,
+            noise,
+            generation: 0,
+            seed,
+        }
+    }
+
+    /// Continues the noise after `generation` generations, so that a run resumed from a checkpoint injects the same noise as without the interruption.
+    pub fn with_generation(mut self, generation: usize) -> Self {
+        self.generation = generation;
+        self
+    }
+
+    /// Flips living cells to dead and all other cells except walls to living, returns whether any cell was flipped.
+    fn inject(&mut self) -> bool {
+        // Seeded by the generation, so that the noise does not depend on the injections before it.
+        // Shifted, so that the noise does not repeat the draws of the random initial field.
+        let mut rng = StdRng::seed_from_u64(
+            self.seed.wrapping_add(1)
+                ^ (self.generation as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15),
+        );
+        let state = self.gameoflife.state();
+        let mut flipped = false;
+        for ((x, y), &cell) in self.gameoflife.snapshot().indexed_iter() {
+            if cell == G::Data::WALL || !rng.gen_bool(self.noise.probability) {
+                continue;
+            }
+            let value = if cell == state {
+                G::Data::default()
+            } else {
+                state
+            };
+            self.gameoflife.set_cell(x, y, value);
+            flipped = true;
+        }
+        flipped
+    }
+}
+
+impl<G: GameOfLife> GameOfLife for Noisy<G> {
+    type Data = G::Data;
+
+    fn new(field: Array2<G::Data>, rules: Rule) -> Self {
+        Self::new(
+            G::new(field, rules),
+            Noise {
+                probability: 0.,
+                every: 1,
+            },
+            0,
+        )
+    }
+
+    fn compute_next_generation(&mut self) -> bool {
+        let changed = self.gameoflife.compute_next_generation();
+        self.generation += 1;
+        if self.generation.is_multiple_of(self.noise.every) {
+            return self.inject() || changed;
+        }
+        changed
+    }
+
+    forward_game_of_life!(gameoflife);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            Noise::from_str("0.01:10"),
+            Ok(Noise {
+                probability: 0.01,
+                every: 10
+            })
+        );
+        for invalid in ["0.01", "0.01:0", "2:10", "-0.5:10", "x:10", "0.01:x"] {
+            assert_eq!(Noise::from_str(invalid), Err(()), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn reproducible() {
+        let noise = Noise {
+            probability: 0.1,
+            every: 3,
+        };
+        let mut field = Array2::zeros((20, 20));
+        field[[5, 5]] = WALL;
+        let gol = || Noisy::new(GameOfLifeStd::new(field.clone(), Rule::default()), noise, 7);
+        let (mut first, mut second) = (gol(), gol());
+
+        // An empty field stays empty until the noise is injected.
+        first.compute_next_generation();
+        first.compute_next_generation();
+        assert!(first
+            .snapshot()
+            .iter()
+            .all(|&cell| cell == 0 || cell == WALL));
+        assert!(first.compute_next_generation());
+        assert_eq!(first.cell(5, 5), Some(WALL));
+
+        for _ in 0..3 {
+            second.compute_next_generation();
+        }
+        assert_eq!(first.snapshot(), second.snapshot());
+        for _ in 0..6 {
+            first.compute_next_generation();
+            second.compute_next_generation();
+        }
+        assert_eq!(first.snapshot(), second.snapshot());
+    }
+}
@@ -0,0 +1,197 @@
+//! # Patterns
+//! Contains well-known patterns of Conway's Game of Life to start from.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use ndarray::{s, Array2};
+
+const GLIDER: &[&str] = &[
+    ".O.", //
+    "..O", //
+    "OOO",
+];
+
+const BLINKER: &[&str] = &["OOO"];
+
+const LWSS: &[&str] = &[
+    ".O..O", //
+    "O....", //
+    "O...O", //
+    "OOOO.",
+];
+
+const PULSAR: &[&str] = &[
+    "..OOO...OOO..",
+    ".............",
+    "O....O.O....O",
+    "O....O.O....O",
+    "O....O.O....O",
+    "..OOO...OOO..",
+    ".............",
+    "..OOO...OOO..",
+    "O....O.O....O",
+    "O....O.O....O",
+    "O....O.O....O",
+    ".............",
+    "..OOO...OOO..",
+];
+
+const GOSPER_GLIDER_GUN: &[&str] = &[
+    "........................O...........",
+    "......................O.O...........",
+    "............OO......OO............OO",
+    "...........O...O....OO............OO",
+    "OO........O.....O...OO..............",
+    "OO........O...O.OO....O.O...........",
+    "..........O.....O.......O...........",
+    "...........O...O....................",
+    "............OO......................",
+];
+
+/// Well-known patterns, stored row by row with `O` as living and `.` as dead cells.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Pattern {
+    Glider,
+    Blinker,
+    Lwss,
+    Pulsar,
+    GosperGliderGun,
+}
+
+impl Pattern {
+    /// Returns the rows of the pattern.
+    fn rows(&self) -> &'static [&'static str] {
+        match *self {
+            Pattern::Glider => GLIDER,
+            Pattern::Blinker => BLINKER,
+            Pattern::Lwss => LWSS,
+            Pattern::Pulsar => PULSAR,
+            Pattern::GosperGliderGun => GOSPER_GLIDER_GUN,
+        }
+    }
+
+    /// Returns the number of columns and rows of the pattern.
+    pub fn size(&self) -> (usize, usize) {
+        let rows = self.rows();
+        (rows[0].len(), rows.len())
+    }
+
+    /// Returns the coordinates (x, y) of the living cells.
+    pub fn cells(&self) -> Vec<(usize, usize)> {
+        self.rows()
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.chars()
+                    .enumerate()
+                    .filter(|(_, cell)| *cell == 'O')
+                    .map(move |(x, _)| (x, y))
+            })
+            .collect()
+    }
+
+    /// Returns a field of size (numx, numy) with the pattern placed in its center with living cells set to `state`.
+    /// Returns None if the pattern does not fit into the field.
+    pub fn place(&self, numx: usize, numy: usize, state: u8) -> Option<Array2<u8>> {
+        let (width, height) = self.size();
+        if width > numx || height > numy {
+            return None;
+        }
+
+        let offset_x = (numx - width) / 2;
+        let offset_y = (numy - height) / 2;
+        let mut field = Array2::zeros((numx, numy));
+        for (x, y) in self.cells() {
+            field[[offset_x + x, offset_y + y]] = state;
+        }
+        Some(field)
+    }
+}
+
+/// Surrounds the field with `padding` dead cells on every side.
+///
+/// ```
+/// use game_of_life::patterns::pad;
+/// use ndarray::Array2;
+///
+/// let padded = pad(&Array2::ones((2, 1)), 1);
+/// assert_eq!(padded.dim(), (4, 3));
+/// assert_eq!(padded.sum(), 2);
+/// ```
+pub fn pad(field: &Array2<u8>, padding: usize) -> Array2<u8> {
+    let (numx, numy) = field.dim();
+    let mut padded = Array2::zeros((numx + 2 * padding, numy + 2 * padding));
+    padded
+        .slice_mut(s![padding..padding + numx, padding..padding + numy])
+        .assign(field);
+    padded
+}
+
+impl FromStr for Pattern {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Pattern, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "glider" => Ok(Pattern::Glider),
+            "blinker" => Ok(Pattern::Blinker),
+            "lwss" => Ok(Pattern::Lwss),
+            "pulsar" => Ok(Pattern::Pulsar),
+            "gosper-glider-gun" => Ok(Pattern::GosperGliderGun),
+            "gun" => Ok(Pattern::GosperGliderGun),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Pattern::Glider => write!(f, "glider"),
+            Pattern::Blinker => write!(f, "blinker"),
+            Pattern::Lwss => write!(f, "lwss"),
+            Pattern::Pulsar => write!(f, "pulsar"),
+            Pattern::GosperGliderGun => write!(f, "gosper-glider-gun"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ndarray::arr2;
+
+    use super::*;
+    use crate::gameoflife::*;
+
+    #[test]
+    fn place_centered() {
+        assert_eq!(
+            Pattern::Glider.place(5, 4, 2),
+            Some(arr2(&[
+                [0, 0, 0, 0],
+                [0, 0, 2, 0],
+                [2, 0, 2, 0],
+                [0, 2, 2, 0],
+                [0, 0, 0, 0]
+            ]))
+        );
+        assert_eq!(Pattern::Pulsar.place(12, 20, 1), None);
+        assert_eq!(Pattern::Pulsar.place(20, 12, 1), None);
+    }
+
+    #[test]
+    fn gosper_glider_gun_emits_gliders() {
+        let field = Pattern::GosperGliderGun.place(80, 60, 1).unwrap();
+        let population = |field: &Array2<u8>| field.iter().filter(|cell| **cell == 1).count();
+        let generations: Vec<_> = GameOfLifeConvolution::new(field, Rule::default())
+            .generations()
+            .step_by(30)
+            .take(4)
+            .collect();
+
+        // The gun has a period of 30 generations and emits a glider of 5 cells every period.
+        for (period, field) in generations.iter().enumerate() {
+            assert_eq!(population(field), 36 + 5 * period);
+        }
+    }
+}
@@ -1,89 +1,741 @@
 //! # Frontends
 //! Contains possible ways to present/plot the Game of Life.
 
+use std::any::Any;
+use std::collections::VecDeque;
 use std::fmt::Display;
 use std::fs::File;
-use std::io::{self, Stdout, Write};
+use std::io::{self, BufWriter, Stdout, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+use std::sync::Arc;
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use gif::{Encoder, EncodingError, Frame, Repeat};
-use indicatif::ProgressBar;
-use ndarray::Array3;
-use termion::event::Key;
-use termion::input::TermRead;
+use indicatif::{DecimalBytes, ProgressBar};
+use ndarray::{aview1, s, Array2, Array3, ArrayView2};
+use termion::color;
+use termion::event::{Event, Key, MouseButton, MouseEvent};
+use termion::input::{Events, MouseTerminal, TermRead};
 use termion::raw::{IntoRawMode, RawTerminal};
 use termion::screen::{AlternateScreen, IntoAlternateScreen, ToMainScreen};
-use termion::{async_stdin, cursor};
+use termion::{async_stdin, cursor, AsyncReader};
 
 use crate::gameoflife::*;
 
 pub enum Presentations {
     Gif,
+    PngSequence,
+    Video,
     Tui,
+    /// Only times the generations, see the `bench` subcommand
+    Benchmark,
+    /// Only saves the last generation as a PNG, see the `snapshot` subcommand
+    Snapshot,
 }
 
 impl Display for Presentations {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match *self {
             Presentations::Gif => write!(f, "GIF"),
+            Presentations::PngSequence => write!(f, "PNG sequence"),
+            Presentations::Video => write!(f, "video"),
             Presentations::Tui => write!(f, "TUI"),
+            Presentations::Benchmark => write!(f, "benchmark"),
+            Presentations::Snapshot => write!(f, "snapshot"),
         }
     }
 }
 
-/// Plot the Game of Life as a GIF using `plotters`
-pub struct GIF<G: GameOfLife> {
+/// Plot the Game of Life as a GIF using `gif`
+pub struct GIF<G: GameOfLife<Data = u8>> {
     gameoflife: G,
+    scale: usize,
+    aspect: Aspect,
+    max_size: Option<u64>,
+    hold_last: Option<Duration>,
+    trail: Option<Trail>,
+    on_generation: Option<OnGeneration<G>>,
 }
 
-impl<G: GameOfLife> GIF<G> {
+impl<G: GameOfLife<Data = u8>> GIF<G> {
     pub fn new(gameoflife: G) -> Self {
-        Self { gameoflife }
+        Self {
+            gameoflife,
+            scale: 1,
+            aspect: Aspect::default(),
+            max_size: None,
+            hold_last: None,
+            trail: None,
+            on_generation: None,
+        }
     }
 
-    /// Starts the Game of Life
-    /// `timer_per_iteration`: ms
-    pub fn start(
+    /// Shows the last frame for `time` instead of the time per iteration, so the end lingers before the GIF loops.
+    /// This is the frame the run ended on, also when it stopped early because the field became stable.
+    /// A GIF cut short by [`GIF::with_max_size`] keeps the normal delay, as its size is only known after writing the frame.
+    pub fn with_hold_last(mut self, time: Duration) -> Self {
+        self.hold_last = Some(time);
+        self
+    }
+
+    /// Stops encoding after the first frame that makes the GIF larger than `bytes`.
+    /// The GIF stays valid, but contains fewer frames.
+    pub fn with_max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// Sets the width and height of every cell in pixels, which is 1 by default.
+    pub fn with_scale(mut self, scale: usize) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Stretches every cell to `aspect.width` x `aspect.height` times the scale, e.g. 1:2 for the proportions of the TUI.
+    pub fn with_aspect(mut self, aspect: Aspect) -> Self {
+        self.aspect = aspect;
+        self
+    }
+
+    /// Lets cells fade out over `frames` frames after they die, see [`Trail`]. 0 disables the trail, which is the default.
+    pub fn with_trail(mut self, frames: u8) -> Self {
+        self.trail = Trail::new(&self.gameoflife, frames);
+        self
+    }
+
+    /// Calls `on_generation` after every computed generation, see [`OnGeneration`].
+    pub fn with_on_generation(mut self, on_generation: impl FnMut(usize, &G) + 'static) -> Self {
+        self.on_generation = Some(Box::new(on_generation));
+        self
+    }
+
+    /// Returns the width and height of the frames in pixels.
+    /// Returns an error if they do not fit into the 16 bits a GIF has for each, see [`GolError::FrameTooLarge`].
+    pub fn frame_size(&self) -> Result<(u16, u16), GolError> {
+        let (cell_width, cell_height) = self.aspect.cell_size(self.scale);
+        let width = self.gameoflife.numx().saturating_mul(cell_width);
+        let height = self.gameoflife.numy().saturating_mul(cell_height);
+        match (width.try_into(), height.try_into()) {
+            (Ok(width), Ok(height)) => Ok((width, height)),
+            _ => Err(GolError::FrameTooLarge { width, height }),
+        }
+    }
+
+    /// Starts the Game of Life and writes the GIF to `writer`, e.g. a `&File`, and returns the number of frames
+    /// Fails with [`GolError::FrameTooLarge`] before writing anything if the frames are too large, see [`GIF::frame_size`].
+    /// If encoding fails or a generation panics, the GIF is ended after the last complete frame, so it stays valid,
+    /// and the error is returned as [`GolError::Encoding`] or [`GolError::Panicked`].
+    /// `timer_per_iteration`: ms, rounded to the GIF's granularity of 10 ms (see [`frame_delay`])
+    /// `stop_when_stable`: ends the GIF early once a generation does not change the field
+    /// `pb`: also shows the current and estimated final size of the GIF
+    pub fn start<W: Write>(
         &mut self,
-        file: &File,
+        writer: W,
         iterations: usize,
         time_per_iteration: Duration,
+        stop_when_stable: bool,
         pb: Option<ProgressBar>,
-    ) -> Result<(), EncodingError> {
-        let mut gif = Encoder::new(
-            file,
-            self.gameoflife.numx().try_into().unwrap(),
-            self.gameoflife.numy().try_into().unwrap(),
-            &[],
-        )?;
-        gif.set_repeat(Repeat::Infinite)?;
-
-        for _ in 0..iterations + 1 {
-            let mut pixels =
-                Array3::<u8>::from_elem((self.gameoflife.numy(), self.gameoflife.numx(), 3), 255);
-            for ((y, x, _), color) in pixels.indexed_iter_mut() {
-                *color = (*color as f32 * self.gameoflife.cell(x, y).unwrap() as f32
-                    / self.gameoflife.state() as f32) as u8;
-            }
-            let pixels: Vec<u8> = pixels.iter().cloned().collect();
-            let mut frame = Frame::from_rgb(
-                self.gameoflife.numx() as u16,
-                self.gameoflife.numy() as u16,
-                &pixels,
+    ) -> Result<usize, GolError> {
+        let (width, height) = self.frame_size()?;
+        let encoding = |e: EncodingError| GolError::Encoding(Arc::new(e));
+        let mut gif =
+            Encoder::new(CountingWriter::new(writer), width, height, &[]).map_err(encoding)?;
+        gif.set_repeat(Repeat::Infinite).map_err(encoding)?;
+        gif.get_mut().commit().map_err(|e| encoding(e.into()))?;
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            self.write_frames(
+                &mut gif,
+                (width, height),
+                iterations,
+                time_per_iteration,
+                stop_when_stable,
+                &pb,
+            )
+        }));
+        // Drop the bytes of a frame that was not completely encoded, then write the trailer after the last complete one.
+        gif.get_mut().discard();
+        let trailer = gif
+            .into_inner()
+            .and_then(|mut writer| writer.commit())
+            .map_err(|e| encoding(e.into()));
+        match result {
+            Ok(Ok(frames)) => trailer.map(|_| frames),
+            Ok(Err(e)) => Err(encoding(e)),
+            Err(payload) => Err(GolError::Panicked(panic_message(payload.as_ref()))),
+        }
+    }
+
+    /// Writes the frames for [`GIF::start`] and returns their number.
+    fn write_frames<W: Write>(
+        &mut self,
+        gif: &mut Encoder<CountingWriter<W>>,
+        (width, height): (u16, u16),
+        iterations: usize,
+        time_per_iteration: Duration,
+        stop_when_stable: bool,
+        pb: &Option<ProgressBar>,
+    ) -> Result<usize, EncodingError> {
+        let (cell_width, cell_height) = self.aspect.cell_size(self.scale);
+        let mut frames = 0;
+        while frames < iterations + 1 {
+            let pixels = render_frame(
+                &self.gameoflife,
+                self.trail.as_ref(),
+                cell_width,
+                cell_height,
             );
-            frame.delay = time_per_iteration.as_millis() as u16 / 10;
+            frames += 1;
+
+            // The next generation is computed before writing the frame to know whether it is the last one.
+            if let Some(ref mut trail) = self.trail {
+                trail.update(&self.gameoflife);
+            }
+            let changed = timed_generation(&mut self.gameoflife, frames, &mut self.on_generation);
+            let stop = stop_when_stable && !changed;
+            let mut frame = Frame::from_rgb(width, height, &pixels);
+            frame.delay = match self.hold_last {
+                Some(time) if stop || frames == iterations + 1 => frame_delay(time),
+                _ => frame_delay(time_per_iteration),
+            };
             gif.write_frame(&frame)?;
+            gif.get_mut().commit()?;
+
+            let size = gif.get_ref().count;
+            if let Some(ref p) = pb {
+                p.inc(1);
+                let estimate = size / frames as u64 * (iterations + 1) as u64;
+                p.set_message(format!(
+                    "{} of ~{}",
+                    DecimalBytes(size),
+                    DecimalBytes(estimate)
+                ));
+            }
+            if stop || self.max_size.is_some_and(|max| size > max) {
+                break;
+            }
+        }
+        if let Some(p) = pb {
+            p.finish();
+        }
+        Ok(frames)
+    }
+}
+
+/// Returns the message of a caught panic, which is a `&str` or a `String` unless the panic was raised with another payload.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => payload
+            .downcast_ref::<String>()
+            .cloned()
+            .unwrap_or_else(|| "unknown panic".to_owned()),
+    }
+}
+
+/// Writer that holds back the bytes written through it until they are committed, and counts the committed ones.
+/// This way, only complete frames reach the inner writer.
+struct CountingWriter<W: Write> {
+    inner: W,
+    pending: Vec<u8>,
+    count: u64,
+}
+
+impl<W: Write> CountingWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            pending: Vec::new(),
+            count: 0,
+        }
+    }
+
+    /// Writes the pending bytes to the inner writer.
+    fn commit(&mut self) -> io::Result<()> {
+        self.inner.write_all(&self.pending)?;
+        self.count += self.pending.len() as u64;
+        self.pending.clear();
+        Ok(())
+    }
+
+    /// Drops the pending bytes, e.g. of a frame whose encoding failed.
+    fn discard(&mut self) {
+        self.pending.clear();
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Converts the time per iteration to a GIF frame delay, which is given in units of 10 ms.
+///
+/// The delay is rounded to the nearest unit and at least 10 ms, as many viewers play frames without delay as fast as possible.
+pub fn frame_delay(time_per_iteration: Duration) -> u16 {
+    let delay = (time_per_iteration.as_millis() + 5) / 10;
+    delay.clamp(1, u16::MAX as u128) as u16
+}
+
+/// Save every generation of the Game of Life as a numbered PNG using `png`
+pub struct PngSequence<G: GameOfLife<Data = u8>> {
+    gameoflife: G,
+    scale: usize,
+    trail: Option<Trail>,
+    on_generation: Option<OnGeneration<G>>,
+}
+
+impl<G: GameOfLife<Data = u8>> PngSequence<G> {
+    pub fn new(gameoflife: G) -> Self {
+        Self {
+            gameoflife,
+            scale: 1,
+            trail: None,
+            on_generation: None,
+        }
+    }
+
+    /// Sets the width and height of every cell in pixels, which is 1 by default.
+    pub fn with_scale(mut self, scale: usize) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Lets cells fade out over `frames` frames after they die, see [`Trail`]. 0 disables the trail, which is the default.
+    pub fn with_trail(mut self, frames: u8) -> Self {
+        self.trail = Trail::new(&self.gameoflife, frames);
+        self
+    }
+
+    /// Calls `on_generation` after every computed generation, see [`OnGeneration`].
+    pub fn with_on_generation(mut self, on_generation: impl FnMut(usize, &G) + 'static) -> Self {
+        self.on_generation = Some(Box::new(on_generation));
+        self
+    }
+
+    /// Starts the Game of Life
+    /// The frames are saved as `gen_00000.png`, `gen_00001.png`, ... in `directory`.
+    /// `stop_when_stable`: stops early once a generation does not change the field
+    pub fn start(
+        &mut self,
+        directory: &Path,
+        iterations: usize,
+        stop_when_stable: bool,
+        pb: Option<ProgressBar>,
+    ) -> Result<(), png::EncodingError> {
+        for generation in 0..iterations + 1 {
+            let file = File::create(directory.join(format!("gen_{generation:05}.png")))?;
+            write_png(
+                BufWriter::new(file),
+                &self.gameoflife,
+                self.trail.as_ref(),
+                self.scale,
+            )?;
 
-            self.gameoflife.compute_next_generation();
+            if let Some(ref mut trail) = self.trail {
+                trail.update(&self.gameoflife);
+            }
+            let changed = timed_generation(
+                &mut self.gameoflife,
+                generation + 1,
+                &mut self.on_generation,
+            );
             if let Some(ref p) = pb {
                 p.inc(1);
             }
+            if stop_when_stable && !changed {
+                break;
+            }
+        }
+        if let Some(ref p) = pb {
+            p.finish();
         }
         Ok(())
     }
 }
 
+/// Save a single generation of the Game of Life as a PNG using `png`, without rendering the generations before it
+pub struct Snapshot<G: GameOfLife<Data = u8>> {
+    gameoflife: G,
+    scale: usize,
+    trail: Option<Trail>,
+    on_generation: Option<OnGeneration<G>>,
+}
+
+impl<G: GameOfLife<Data = u8>> Snapshot<G> {
+    pub fn new(gameoflife: G) -> Self {
+        Self {
+            gameoflife,
+            scale: 1,
+            trail: None,
+            on_generation: None,
+        }
+    }
+
+    /// Sets the width and height of every cell in pixels, which is 1 by default.
+    pub fn with_scale(mut self, scale: usize) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Lets cells fade out over `frames` frames after they die, see [`Trail`]. 0 disables the trail, which is the default.
+    pub fn with_trail(mut self, frames: u8) -> Self {
+        self.trail = Trail::new(&self.gameoflife, frames);
+        self
+    }
+
+    /// Calls `on_generation` after every computed generation, see [`OnGeneration`].
+    pub fn with_on_generation(mut self, on_generation: impl FnMut(usize, &G) + 'static) -> Self {
+        self.on_generation = Some(Box::new(on_generation));
+        self
+    }
+
+    /// Starts the Game of Life and saves generation `at` to `path`.
+    /// Generations are counted from 0, which is the initial field, so `at` is the number of computed generations.
+    /// `stop_when_stable`: stops early once a generation does not change the field, which then is the same at `at`
+    pub fn start(
+        &mut self,
+        path: &Path,
+        at: usize,
+        stop_when_stable: bool,
+        pb: Option<ProgressBar>,
+    ) -> Result<(), png::EncodingError> {
+        self.write(
+            BufWriter::new(File::create(path)?),
+            at,
+            stop_when_stable,
+            pb,
+        )
+    }
+
+    fn write<W: Write>(
+        &mut self,
+        writer: W,
+        at: usize,
+        stop_when_stable: bool,
+        pb: Option<ProgressBar>,
+    ) -> Result<(), png::EncodingError> {
+        for generation in 1..at + 1 {
+            if let Some(ref mut trail) = self.trail {
+                trail.update(&self.gameoflife);
+            }
+            let changed =
+                timed_generation(&mut self.gameoflife, generation, &mut self.on_generation);
+            if let Some(ref p) = pb {
+                p.inc(1);
+            }
+            if stop_when_stable && !changed {
+                break;
+            }
+        }
+        if let Some(ref p) = pb {
+            p.finish();
+        }
+        write_png(writer, &self.gameoflife, self.trail.as_ref(), self.scale)
+    }
+}
+
+/// Encode the Game of Life as a video by piping the frames to `ffmpeg`
+pub struct Video<G: GameOfLife<Data = u8>> {
+    gameoflife: G,
+    scale: usize,
+    trail: Option<Trail>,
+    on_generation: Option<OnGeneration<G>>,
+}
+
+impl<G: GameOfLife<Data = u8>> Video<G> {
+    pub fn new(gameoflife: G) -> Self {
+        Self {
+            gameoflife,
+            scale: 1,
+            trail: None,
+            on_generation: None,
+        }
+    }
+
+    /// Sets the width and height of every cell in pixels, which is 1 by default.
+    pub fn with_scale(mut self, scale: usize) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Lets cells fade out over `frames` frames after they die, see [`Trail`]. 0 disables the trail, which is the default.
+    pub fn with_trail(mut self, frames: u8) -> Self {
+        self.trail = Trail::new(&self.gameoflife, frames);
+        self
+    }
+
+    /// Calls `on_generation` after every computed generation, see [`OnGeneration`].
+    pub fn with_on_generation(mut self, on_generation: impl FnMut(usize, &G) + 'static) -> Self {
+        self.on_generation = Some(Box::new(on_generation));
+        self
+    }
+
+    /// Starts the Game of Life and encodes the video to `output`, whose extension selects the format (e.g. `.mp4` or `.webm`)
+    /// `timer_per_iteration`: ms, sets the frame rate
+    /// `stop_when_stable`: ends the video early once a generation does not change the field
+    /// Returns an error of kind `NotFound` if `ffmpeg` is not on the `PATH`.
+    pub fn start(
+        &mut self,
+        output: &Path,
+        iterations: usize,
+        time_per_iteration: Duration,
+        stop_when_stable: bool,
+        pb: Option<ProgressBar>,
+    ) -> io::Result<()> {
+        let mut ffmpeg = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error"])
+            .args(["-f", "rawvideo", "-pixel_format", "rgb24"])
+            .arg("-video_size")
+            .arg(format!(
+                "{}x{}",
+                self.gameoflife.numx() * self.scale,
+                self.gameoflife.numy() * self.scale
+            ))
+            .arg("-framerate")
+            .arg(format!("1000/{}", time_per_iteration.as_millis().max(1)))
+            .args(["-i", "-"])
+            // Most encoders need even dimensions and players expect YUV 4:2:0.
+            .args([
+                "-vf",
+                "pad=ceil(iw/2)*2:ceil(ih/2)*2",
+                "-pix_fmt",
+                "yuv420p",
+            ])
+            .arg(output)
+            .stdin(Stdio::piped())
+            .spawn()?;
+
+        let mut stdin = ffmpeg.stdin.take().unwrap();
+        let written = self.write_frames(&mut stdin, iterations, stop_when_stable, pb);
+        drop(stdin);
+
+        // A failing `ffmpeg` closes the pipe, so its exit status explains a failed write best.
+        let status = ffmpeg.wait()?;
+        if !status.success() {
+            return Err(io::Error::other(format!("ffmpeg exited with {status}")));
+        }
+        written
+    }
+
+    /// Writes the raw RGB frames of all generations.
+    fn write_frames<W: Write>(
+        &mut self,
+        mut writer: W,
+        iterations: usize,
+        stop_when_stable: bool,
+        pb: Option<ProgressBar>,
+    ) -> io::Result<()> {
+        for generation in 0..iterations + 1 {
+            writer.write_all(&render_frame(
+                &self.gameoflife,
+                self.trail.as_ref(),
+                self.scale,
+                self.scale,
+            ))?;
+
+            if let Some(ref mut trail) = self.trail {
+                trail.update(&self.gameoflife);
+            }
+            let changed = timed_generation(
+                &mut self.gameoflife,
+                generation + 1,
+                &mut self.on_generation,
+            );
+            if let Some(ref p) = pb {
+                p.inc(1);
+            }
+            if stop_when_stable && !changed {
+                break;
+            }
+        }
+        if let Some(ref p) = pb {
+            p.finish();
+        }
+        Ok(())
+    }
+}
+
+/// Closure that the presentations call after every computed generation with its number, counted from 1, and the Game of Life.
+/// It can, e.g., collect statistics without changing the presentation.
+///
+/// ```
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+/// use std::time::Duration;
+///
+/// use game_of_life::gameoflife::*;
+/// use game_of_life::patterns::Pattern;
+/// use game_of_life::presentation::GIF;
+///
+/// let populations = Rc::new(RefCell::new(Vec::new()));
+/// let field = Pattern::Glider.place(10, 10, 1).unwrap();
+/// let mut gif = GIF::new(GameOfLifeStd::new(field, Rule::default())).with_on_generation({
+///     let populations = Rc::clone(&populations);
+///     move |_, gol| {
+///         let population = gol.snapshot().iter().filter(|&&cell| cell == 1).count();
+///         populations.borrow_mut().push(population);
+///     }
+/// });
+/// gif.start(Vec::new(), 3, Duration::from_millis(100), false, None)
+///     .unwrap();
+/// assert_eq!(*populations.borrow(), [5, 5, 5, 5]);
+/// ```
+pub type OnGeneration<G> = Box<dyn FnMut(usize, &G)>;
+
+/// Computes the next generation, logs how long it took, and calls `on_generation` if there is one.
+fn timed_generation<G: GameOfLife<Data = u8>>(
+    gameoflife: &mut G,
+    generation: usize,
+    on_generation: &mut Option<OnGeneration<G>>,
+) -> bool {
+    let start = Instant::now();
+    let changed = gameoflife.compute_next_generation();
+    log::debug!("Computed a generation in {:.2?}.", start.elapsed());
+    if let Some(on_generation) = on_generation {
+        on_generation(generation, gameoflife);
+    }
+    changed
+}
+
+/// Encodes the current frame as an RGB PNG, see [`render_frame`].
+fn write_png<G: GameOfLife<Data = u8>, W: Write>(
+    writer: W,
+    gameoflife: &G,
+    trail: Option<&Trail>,
+    scale: usize,
+) -> Result<(), png::EncodingError> {
+    let mut encoder = png::Encoder::new(
+        writer,
+        (gameoflife.numx() * scale).try_into().unwrap(),
+        (gameoflife.numy() * scale).try_into().unwrap(),
+    );
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()?
+        .write_image_data(&render_frame(gameoflife, trail, scale, scale))
+}
+
+/// Color of wall cells in the rendered frames
+const WALL_COLOR: [u8; 3] = [200, 60, 60];
+/// Color of cells that just died with a trail, which fades to black
+const TRAIL_COLOR: [u8; 3] = [60, 110, 200];
+
+/// Fading trail of recently dead cells, which is kept by the presentation and does not affect the Game of Life.
+///
+/// Every cell has a heat, which is set to `frames` while the cell is alive and decreases by one in every frame after it died.
+/// Dead cells with heat are drawn in [`TRAIL_COLOR`] dimmed by their heat instead of black.
+struct Trail {
+    heat: Array2<u8>,
+    frames: u8,
+}
+
+impl Trail {
+    /// Returns None for 0 frames, which means no trail.
+    fn new<G: GameOfLife<Data = u8>>(gameoflife: &G, frames: u8) -> Option<Self> {
+        (frames > 0).then(|| Self {
+            heat: Array2::zeros((gameoflife.numx(), gameoflife.numy())),
+            frames,
+        })
+    }
+
+    /// Heats up the living cells of the current generation and cools down all others, called before every new generation.
+    fn update<G: GameOfLife<Data = u8>>(&mut self, gameoflife: &G) {
+        let (state, frames) = (gameoflife.state(), self.frames);
+        for ((x, y), heat) in self.heat.indexed_iter_mut() {
+            *heat = if gameoflife.cell(x, y) == Some(state) {
+                frames
+            } else {
+                heat.saturating_sub(1)
+            };
+        }
+    }
+
+    /// Returns the color of the trail at (x, y), or None if it has faded out.
+    fn color(&self, x: usize, y: usize) -> Option<[u8; 3]> {
+        let heat = self.heat[[x, y]] as u32;
+        (heat > 0).then(|| TRAIL_COLOR.map(|c| (c as u32 * heat / self.frames as u32) as u8))
+    }
+
+    fn clear(&mut self) {
+        self.heat.fill(0);
+    }
+}
+
+/// Renders the field as RGB pixels row by row, fading the cells by their state and dead cells by the trail if there is one.
+/// Every cell becomes a block of `cell_width` x `cell_height` pixels.
+fn render_frame<G: GameOfLife<Data = u8>>(
+    gameoflife: &G,
+    trail: Option<&Trail>,
+    cell_width: usize,
+    cell_height: usize,
+) -> Vec<u8> {
+    render_field(
+        gameoflife.snapshot().view(),
+        gameoflife.state(),
+        trail,
+        cell_width,
+        cell_height,
+    )
+}
+
+/// Like [`render_frame`], but for a field indexed by `[x, y]` whose living cells have the value `state`.
+fn render_field(
+    field: ArrayView2<'_, u8>,
+    state: u8,
+    trail: Option<&Trail>,
+    cell_width: usize,
+    cell_height: usize,
+) -> Vec<u8> {
+    let (numx, numy) = field.dim();
+    let mut pixels = Array3::<u8>::zeros((numy * cell_height, numx * cell_width, 3));
+    for y in 0..numy {
+        for x in 0..numx {
+            let cell = field[[x, y]];
+            let color = match cell {
+                WALL => WALL_COLOR,
+                0 => trail.and_then(|trail| trail.color(x, y)).unwrap_or([0; 3]),
+                _ => [(255. * cell as f32 / state as f32) as u8; 3],
+            };
+            pixels
+                .slice_mut(s![
+                    y * cell_height..(y + 1) * cell_height,
+                    x * cell_width..(x + 1) * cell_width,
+                    ..
+                ])
+                .assign(&aview1(&color));
+        }
+    }
+    pixels.into_raw_vec()
+}
+
+impl Board {
+    /// Renders the board as a binary PPM (P6) in the colors of the frames, with every cell as a block of `scale` x `scale` pixels.
+    /// Unlike a GIF, the bytes do not depend on the compression of an encoder, so they can be compared to a golden image.
+    ///
+    /// Panics if `cells` does not have `numx * numy` values or the state does not fit into a `u8`, see [`check_state`].
+    pub fn to_ppm(&self, scale: usize) -> Vec<u8> {
+        let field = ArrayView2::from_shape((self.numx, self.numy), &self.cells)
+            .expect("the cells should match the dimensions of the board");
+        let state = check_state::<u8>(self.rule.state).unwrap_or_else(|e| panic!("{e}"));
+        let mut ppm =
+            format!("P6\n{} {}\n255\n", self.numx * scale, self.numy * scale).into_bytes();
+        ppm.extend(render_field(field, state, None, scale, scale));
+        ppm
+    }
+}
+
 const HORZ_BOUNDARY: &str = "─";
 const VERT_BOUNDARY: &str = "│";
 const TOP_LEFT_CORNER: &str = "┌";
@@ -91,14 +743,80 @@ const TOP_RIGHT_CORNER: &str = "┐";
 const BOTTOM_LEFT_CORNER: &str = "└";
 const BOTTOM_RIGHT_CORNER: &str = "┘";
 const CONCEALED: &str = "▒";
+const WALL_GLYPH: &str = "█";
+const TRAIL_GLYPH: &str = "░";
+const OLDEST_LABEL: &str = " oldest ";
+/// Longest time the TUI waits between two checks for input, about one frame at 60 Hz
+const POLLING_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Color of walls in the terminal, red in the 6x6x6 cube of the 256 ANSI colors
+const WALL_SHADE: u8 = 16 + 36 * 4 + 6 + 1;
+/// Color of the trail in the terminal, blue in the 6x6x6 cube of the 256 ANSI colors
+const TRAIL_SHADE: u8 = 16 + 36 + 6 * 2 + 4;
+
+/// Returns the glyph representing a cell in the terminal.
+fn glyph(cell: u8) -> &'static [u8] {
+    match cell {
+        0 => b" ",
+        WALL => WALL_GLYPH.as_bytes(),
+        _ => CONCEALED.as_bytes(),
+    }
+}
+
+/// Returns the ANSI color of a cell in the terminal like the shading of the GIF, from white for living cells to dark gray for the last dying state.
+/// Returns None for dead cells without a trail, which are blank anyway.
+fn shade(cell: u8, state: u8, trail: bool) -> Option<u8> {
+    match cell {
+        0 if trail => Some(TRAIL_SHADE),
+        0 => None,
+        WALL => Some(WALL_SHADE),
+        // The darkest of the 24 grays are hard to tell apart from the background.
+        _ => Some(color::AnsiValue::grayscale(6 + (17 * cell as u32 / state as u32) as u8).0),
+    }
+}
+
+/// Actions the user can trigger in the TUI
+enum Action {
+    Quit,
+    Pause,
+    Step,
+    Faster,
+    Slower,
+    Reset,
+    StepBack,
+    /// Moves the view by a quarter of its size in the given direction.
+    Pan(isize, isize),
+}
 
 /// Plot the Game of Life in the terminal using `termion`
-pub struct TUI<G: GameOfLife> {
+///
+/// Keys:
+/// - `q`: quit
+/// - space: pause/resume
+/// - `n`: pause and advance a single generation
+/// - `+`/`-`: halve/double the time per iteration
+/// - `r`: restore the initial field and restart the count of iterations
+/// - `b`: pause and go back to the previous generation, as long as it is still in the history
+/// - arrow keys: move the view across fields larger than the terminal
+///
+/// Left-click on cells to toggle them and right-click to toggle walls.
+pub struct TUI<G: GameOfLife<Data = u8>> {
     gol: G,
-    screen: AlternateScreen<RawTerminal<Stdout>>,
+    initial: G,
+    history: VecDeque<Array2<u8>>,
+    history_depth: usize,
+    trail: Option<Trail>,
+    /// Whether the cells are shaded by their state with ANSI colors
+    color: bool,
+    on_generation: Option<OnGeneration<G>>,
+    /// Cell shown in the upper left corner of the view
+    offset: (usize, usize),
+    /// Number of columns and rows of the field that fit into the terminal
+    visible: (usize, usize),
+    screen: MouseTerminal<AlternateScreen<RawTerminal<Stdout>>>,
 }
 
-impl<G: GameOfLife> TUI<G> {
+impl<G: GameOfLife<Data = u8> + Clone> TUI<G> {
     pub fn new(gol: G) -> Self {
         std::panic::set_hook(Box::new(move |info| {
             write!(
@@ -111,43 +829,252 @@ impl<G: GameOfLife> TUI<G> {
         }));
 
         let screen = io::stdout().into_raw_mode().unwrap();
-        let screen = screen.into_alternate_screen().unwrap();
+        let screen = MouseTerminal::from(screen.into_alternate_screen().unwrap());
+        let visible = viewport_size(gol.numx(), gol.numy(), termion::terminal_size().ok());
 
-        Self { gol, screen }
+        Self {
+            initial: gol.clone(),
+            gol,
+            history: VecDeque::new(),
+            history_depth: 100,
+            trail: None,
+            color: true,
+            on_generation: None,
+            offset: (0, 0),
+            visible,
+            screen,
+        }
+    }
+
+    /// Sets how many previous generations are kept to step back to, which is 100 by default.
+    pub fn with_history(mut self, depth: usize) -> Self {
+        self.history_depth = depth;
+        self
+    }
+
+    /// Shows cells that died within the last `frames` generations as a shade, see [`Trail`]. 0 disables the trail, which is the default.
+    pub fn with_trail(mut self, frames: u8) -> Self {
+        self.trail = Trail::new(&self.gol, frames);
+        self
+    }
+
+    /// Calls `on_generation` after every computed generation, see [`OnGeneration`].
+    pub fn with_on_generation(mut self, on_generation: impl FnMut(usize, &G) + 'static) -> Self {
+        self.on_generation = Some(Box::new(on_generation));
+        self
+    }
+
+    /// Shades the cells by their state, walls, and the trail with the 256 ANSI colors, which is the default.
+    /// Without colors, the cells are only told apart by their glyphs.
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
     }
 
     /// Starts the Game of Life
+    /// `iterations`: None runs until the user quits
     /// `timer_per_iteration`: ms
-    pub fn start(&mut self, iterations: usize, time_per_iteration: Duration) -> io::Result<()> {
+    /// `stop_when_stable`: pauses once a generation does not change the field
+    pub fn start(
+        &mut self,
+        iterations: Option<usize>,
+        time_per_iteration: Duration,
+        stop_when_stable: bool,
+    ) -> io::Result<()> {
         self.initialize_field()?;
-        let mut stdin = async_stdin().keys();
-        let polling_time = 200;
-        let sleep_how_often = time_per_iteration.as_millis() / polling_time;
-        let remaining_sleep = time_per_iteration.as_millis() - sleep_how_often * polling_time;
-
-        for _ in 0..iterations + 1 {
-            self.gol.compute_next_generation();
-            self.draw_field()?;
-            for _ in 0..sleep_how_often {
-                for key in &mut stdin {
-                    let key = key?;
-                    if let Key::Char('q') = key {
-                        return Ok(());
+        let mut events = async_stdin().events();
+        let mut time_per_iteration = time_per_iteration;
+        let mut paused = false;
+        let mut step = false;
+        let mut iteration = 0;
+        let mut last_frame = Instant::now();
+
+        while iterations.is_none_or(|iterations| iteration < iterations + 1) {
+            if !paused || step {
+                self.record_generation();
+                if let Some(ref mut trail) = self.trail {
+                    trail.update(&self.gol);
+                }
+                // Not timed, as the log would draw over the field.
+                let changed = self.gol.compute_next_generation();
+                if let Some(ref mut on_generation) = self.on_generation {
+                    on_generation(iteration + 1, &self.gol);
+                }
+                if !changed && stop_when_stable {
+                    paused = true;
+                }
+                self.draw_field()?;
+                self.draw_oldest_indicator(false)?;
+                iteration += 1;
+                step = false;
+                last_frame = Instant::now();
+            }
+
+            // Poll for input until the next generation is due, so that keys take effect within
+            // a polling interval regardless of the time per iteration.
+            loop {
+                match self.poll_action(&mut events)? {
+                    Some(Action::Quit) => return Ok(()),
+                    Some(Action::Pause) => paused = !paused,
+                    Some(Action::Step) => {
+                        paused = true;
+                        step = true;
+                        break;
+                    }
+                    Some(Action::Faster) => {
+                        time_per_iteration = (time_per_iteration / 2).max(Duration::from_millis(10))
                     }
+                    Some(Action::Slower) => {
+                        time_per_iteration = (time_per_iteration * 2).min(Duration::from_secs(60))
+                    }
+                    Some(Action::Reset) => {
+                        self.gol = self.initial.clone();
+                        self.history.clear();
+                        if let Some(ref mut trail) = self.trail {
+                            trail.clear();
+                        }
+                        iteration = 0;
+                        self.draw_field()?;
+                        self.draw_oldest_indicator(false)?;
+                    }
+                    Some(Action::Pan(dx, dy)) => {
+                        let (numx, numy) = (self.gol.numx(), self.gol.numy());
+                        let offset = (
+                            pan(self.offset.0, dx, self.visible.0, numx),
+                            pan(self.offset.1, dy, self.visible.1, numy),
+                        );
+                        if offset != self.offset {
+                            self.offset = offset;
+                            self.draw_field()?;
+                        }
+                    }
+                    Some(Action::StepBack) => {
+                        paused = true;
+                        if self.step_back() {
+                            iteration = iteration.saturating_sub(1);
+                            self.draw_field()?;
+                        }
+                        self.draw_oldest_indicator(self.history.is_empty())?;
+                    }
+                    None => {}
+                }
+                match poll_timeout(last_frame + time_per_iteration, Instant::now(), paused) {
+                    Some(timeout) => sleep(timeout),
+                    None => break,
                 }
-                sleep(Duration::from_millis(polling_time as u64))
             }
-            sleep(Duration::from_millis(remaining_sleep as u64))
         }
 
         Ok(())
     }
 
+    /// Reads at most one pending input event without blocking.
+    /// Mouse clicks are handled directly, keys are returned as the triggered action.
+    fn poll_action(&mut self, events: &mut Events<AsyncReader>) -> io::Result<Option<Action>> {
+        let Some(event) = events.next() else {
+            return Ok(None);
+        };
+        let action = match event? {
+            Event::Key(Key::Char('q')) => Some(Action::Quit),
+            Event::Key(Key::Char(' ')) => Some(Action::Pause),
+            Event::Key(Key::Char('n')) => Some(Action::Step),
+            Event::Key(Key::Char('+')) => Some(Action::Faster),
+            Event::Key(Key::Char('-')) => Some(Action::Slower),
+            Event::Key(Key::Char('r')) => Some(Action::Reset),
+            Event::Key(Key::Char('b')) => Some(Action::StepBack),
+            Event::Key(Key::Left) => Some(Action::Pan(-1, 0)),
+            Event::Key(Key::Right) => Some(Action::Pan(1, 0)),
+            Event::Key(Key::Up) => Some(Action::Pan(0, -1)),
+            Event::Key(Key::Down) => Some(Action::Pan(0, 1)),
+            Event::Mouse(MouseEvent::Press(MouseButton::Right, column, row)) => {
+                self.toggle_cell(column, row, WALL)?;
+                None
+            }
+            Event::Mouse(MouseEvent::Press(_, column, row)) => {
+                self.toggle_cell(column, row, self.gol.state())?;
+                None
+            }
+            _ => None,
+        };
+        Ok(action)
+    }
+
+    /// Saves the current generation before it is replaced, dropping the oldest one if the history is full.
+    fn record_generation(&mut self) {
+        if self.history_depth == 0 {
+            return;
+        }
+        if self.history.len() == self.history_depth {
+            self.history.pop_front();
+        }
+        self.history.push_back(self.gol.snapshot());
+    }
+
+    /// Restores the previous generation from the history and returns whether there was one.
+    /// The game of life is not reversible, so only recorded generations can be restored.
+    fn step_back(&mut self) -> bool {
+        let Some(field) = self.history.pop_back() else {
+            return false;
+        };
+        for ((x, y), &cell) in field.indexed_iter() {
+            if self.gol.cell(x, y) != Some(cell) {
+                self.gol.set_cell(x, y, cell);
+            }
+        }
+        true
+    }
+
+    /// Shows in the upper part of the frame whether the oldest recorded generation is displayed, or restores the frame.
+    fn draw_oldest_indicator(&mut self, oldest: bool) -> io::Result<()> {
+        let width = self.visible.0;
+        if width < OLDEST_LABEL.chars().count() {
+            return Ok(());
+        }
+        write!(self.screen, "{}", cursor::Goto(2, 1))?;
+        if oldest {
+            self.screen.write_all(OLDEST_LABEL.as_bytes())?;
+        } else {
+            for _ in 0..OLDEST_LABEL.chars().count() {
+                self.screen.write_all(HORZ_BOUNDARY.as_bytes())?;
+            }
+        }
+        self.screen.flush()
+    }
+
+    /// Sets the cell at the (one-based) terminal position to `value` if it is dead, else kills it, and redraws it.
+    fn toggle_cell(&mut self, column: u16, row: u16, value: u8) -> io::Result<()> {
+        // The field is offset by the cursor being one-based and the frame.
+        let (Some(x), Some(y)) = (column.checked_sub(2), row.checked_sub(2)) else {
+            return Ok(());
+        };
+        let (x, y) = (x as usize, y as usize);
+        if x >= self.visible.0 || y >= self.visible.1 {
+            return Ok(());
+        }
+        let (x, y) = (self.offset.0 + x, self.offset.1 + y);
+        let Some(cell) = self.gol.cell(x, y) else {
+            return Ok(());
+        };
+
+        let value = if cell == 0 { value } else { 0 };
+        self.gol.set_cell(x, y, value);
+
+        write!(self.screen, "{}", cursor::Goto(column, row))?;
+        let shade = shade(value, self.gol.state(), false).filter(|_| self.color);
+        if let Some(shade) = shade {
+            write!(self.screen, "{}", color::Fg(color::AnsiValue(shade)))?;
+        }
+        self.screen.write_all(glyph(value))?;
+        if shade.is_some() {
+            write!(self.screen, "{}", color::Fg(color::Reset))?;
+        }
+        self.screen.flush()
+    }
+
     /// Initializes the TUI
     fn initialize_field(&mut self) -> std::io::Result<()> {
         let screen = &mut self.screen;
-        let width = self.gol.numx();
-        let height = self.gol.numy();
+        let (width, height) = self.visible;
 
         write!(screen, "{}", cursor::Hide)?;
 
@@ -159,19 +1086,10 @@ impl<G: GameOfLife> TUI<G> {
         screen.write_all(TOP_RIGHT_CORNER.as_bytes())?;
         screen.write_all(b"\n\r")?;
 
-        for y in 0..height {
-            // The left part of the frame
+        // The left and right part of the frame, the cells are drawn by `draw_field`.
+        for _ in 0..height {
             screen.write_all(VERT_BOUNDARY.as_bytes())?;
-
-            for x in 0..width {
-                if self.gol.cell(x, y).unwrap() > 0 {
-                    screen.write_all(CONCEALED.as_bytes())?;
-                } else {
-                    screen.write_all(b" ")?;
-                }
-            }
-
-            // The right part of the frame.
+            write!(screen, "{}", cursor::Right(width as u16))?;
             screen.write_all(VERT_BOUNDARY.as_bytes())?;
             screen.write_all(b"\n\r")?;
         }
@@ -183,25 +1101,44 @@ impl<G: GameOfLife> TUI<G> {
         }
         screen.write_all(BOTTOM_RIGHT_CORNER.as_bytes())?;
 
-        screen.flush()?;
-
-        Ok(())
+        self.draw_field()
     }
 
+    /// Draws the cells in the view.
     fn draw_field(&mut self) -> std::io::Result<()> {
         let screen = &mut self.screen;
-        let width = u16::try_from(self.gol.numx()).unwrap();
-        let height = u16::try_from(self.gol.numy()).unwrap();
-
-        for y in 0..height {
-            write!(screen, "{}", cursor::Goto(2, y + 2))?;
-            for x in 0..width {
-                if self.gol.cell(x as usize, y as usize).unwrap() > 0 {
-                    screen.write_all(CONCEALED.as_bytes())?;
+        let (width, height) = self.visible;
+        let (offset_x, offset_y) = self.offset;
+        let state = self.gol.state();
+
+        for row in 0..height {
+            write!(screen, "{}", cursor::Goto(2, row as u16 + 2))?;
+            // Colors are only switched between cells of different shades and reset before the frame.
+            let mut current = None;
+            for column in 0..width {
+                let (x, y) = (offset_x + column, offset_y + row);
+                let cell = self.gol.cell(x, y).unwrap();
+                let trail = self
+                    .trail
+                    .as_ref()
+                    .is_some_and(|trail| trail.heat[[x, y]] > 0);
+                if self.color {
+                    if let Some(shade) = shade(cell, state, trail) {
+                        if current != Some(shade) {
+                            write!(screen, "{}", color::Fg(color::AnsiValue(shade)))?;
+                            current = Some(shade);
+                        }
+                    }
+                }
+                if cell == 0 && trail {
+                    screen.write_all(TRAIL_GLYPH.as_bytes())?;
                 } else {
-                    screen.write_all(b" ")?;
+                    screen.write_all(glyph(cell))?;
                 }
             }
+            if current.is_some() {
+                write!(screen, "{}", color::Fg(color::Reset))?;
+            }
         }
         screen.flush()?;
 
@@ -209,19 +1146,557 @@ impl<G: GameOfLife> TUI<G> {
     }
 }
 
-impl<G: GameOfLife> Drop for TUI<G> {
+impl<G: GameOfLife<Data = u8>> Drop for TUI<G> {
     fn drop(&mut self) {
         write!(self.screen, "{}", cursor::Show).unwrap();
     }
 }
 
+/// Size of the rendered frames in pixels, written as `WIDTHxHEIGHT`, e.g. `1920x1080`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Resolution {
+    /// Returns the number of columns and rows that fit into the resolution with cells of `cell_width` x `cell_height` pixels, but at least one each.
+    pub fn field_size(&self, cell_width: u32, cell_height: u32) -> (u32, u32) {
+        (
+            (self.width / cell_width).max(1),
+            (self.height / cell_height).max(1),
+        )
+    }
+}
+
+impl FromStr for Resolution {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Resolution, Self::Err> {
+        let input = input.to_lowercase();
+        let (width, height) = input.split_once('x').ok_or(())?;
+        match (width.trim().parse(), height.trim().parse()) {
+            (Ok(width), Ok(height)) if width > 0 && height > 0 => Ok(Resolution { width, height }),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for Resolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+/// Ratio of the width to the height of the cells in a GIF, written as `WIDTH:HEIGHT`, e.g. `1:2`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aspect {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Aspect {
+    /// Returns the width and height of a cell in pixels when scaled by `scale`.
+    pub fn cell_size(&self, scale: usize) -> (usize, usize) {
+        (scale * self.width as usize, scale * self.height as usize)
+    }
+}
+
+impl Default for Aspect {
+    fn default() -> Self {
+        Self {
+            width: 1,
+            height: 1,
+        }
+    }
+}
+
+impl FromStr for Aspect {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Aspect, Self::Err> {
+        let (width, height) = input.split_once(':').ok_or(())?;
+        match (width.trim().parse(), height.trim().parse()) {
+            (Ok(width), Ok(height)) if width > 0 && height > 0 => Ok(Aspect { width, height }),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for Aspect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.width, self.height)
+    }
+}
+
 /// Returns the user preference for the field size if specified, else the terminal size.
+/// The field fits into the terminal together with its frame, but has at least one cell per dimension.
+/// Without a terminal, e.g. in headless environments, the field defaults to 10x10.
 pub fn get_size(numx: Option<u32>, numy: Option<u32>) -> (u32, u32) {
-    let termsize = termion::terminal_size().ok();
-    let termwidth = termsize.map(|(w, _)| w - 2);
-    let termheight = termsize.map(|(_, h)| h - 2);
+    size_in_terminal(numx, numy, termion::terminal_size().ok())
+}
+
+/// Returns how many columns and rows of a field of `numx` x `numy` cells the TUI shows in a terminal of size `termsize`.
+/// Without a terminal size, the whole field is shown.
+fn viewport_size(numx: usize, numy: usize, termsize: Option<(u16, u16)>) -> (usize, usize) {
+    match termsize {
+        Some(_) => {
+            let (width, height) = size_in_terminal(None, None, termsize);
+            (numx.min(width as usize), numy.min(height as usize))
+        }
+        None => (numx, numy),
+    }
+}
+
+/// Returns how long the TUI sleeps before polling for input again, or None once the generation at `next_frame` is due.
+/// While paused, no generation is ever due.
+fn poll_timeout(next_frame: Instant, now: Instant, paused: bool) -> Option<Duration> {
+    if paused {
+        return Some(POLLING_INTERVAL);
+    }
+    let remaining = next_frame.saturating_duration_since(now);
+    (!remaining.is_zero()).then(|| remaining.min(POLLING_INTERVAL))
+}
+
+/// Moves the start of a view of `visible` cells by a quarter of its size in `direction`, keeping it within `len` cells.
+fn pan(offset: usize, direction: isize, visible: usize, len: usize) -> usize {
+    let step = (visible / 4).max(1) as isize;
+    let max = len.saturating_sub(visible) as isize;
+    (offset as isize + direction * step).clamp(0, max) as usize
+}
+
+/// See [`get_size`], with the terminal size passed in.
+fn size_in_terminal(
+    numx: Option<u32>,
+    numy: Option<u32>,
+    termsize: Option<(u16, u16)>,
+) -> (u32, u32) {
+    // The frame takes up one character on every side.
+    let inner = |length: u16| length.saturating_sub(2).max(1) as u32;
     (
-        numx.or(termwidth.map(|elem| elem as u32)).unwrap_or(10),
-        numy.or(termheight.map(|elem| elem as u32)).unwrap_or(10),
+        numx.or(termsize.map(|(width, _)| inner(width)))
+            .unwrap_or(10),
+        numy.or(termsize.map(|(_, height)| inner(height)))
+            .unwrap_or(10),
     )
 }
+
+#[cfg(test)]
+mod test {
+    use ndarray::Array2;
+
+    use super::*;
+    use crate::patterns::Pattern;
+
+    #[test]
+    fn frame_delays() {
+        for (millis, delay) in [
+            (0, 1),
+            (4, 1),
+            (14, 1),
+            (15, 2),
+            (55, 6),
+            (500, 50),
+            (654_999, 65_500),
+            (u64::MAX, u16::MAX),
+        ] {
+            assert_eq!(
+                frame_delay(Duration::from_millis(millis)),
+                delay,
+                "{millis} ms"
+            );
+        }
+    }
+
+    #[test]
+    fn non_square_gif() {
+        let (numx, numy) = (20, 10);
+        let mut field = Array2::zeros((numx, numy));
+        field[[19, 0]] = 1;
+        field[[0, 9]] = 1;
+        field[[3, 2]] = WALL;
+        let gol = GameOfLifeConvolution::new(field, Rule::default());
+
+        let pixels = render_frame(&gol, None, 1, 1);
+        assert_eq!(pixels.len(), numx * numy * 3);
+        let pixel = |x: usize, y: usize| &pixels[(y * numx + x) * 3..][..3];
+        assert_eq!(pixel(19, 0), [255; 3]);
+        assert_eq!(pixel(0, 9), [255; 3]);
+        assert_eq!(pixel(3, 2), WALL_COLOR);
+        assert_eq!(pixel(0, 0), [0; 3]);
+        assert_eq!(pixel(9, 0), [0; 3]);
+
+        let mut output = Vec::new();
+        GIF::new(gol)
+            .start(&mut output, 0, Duration::from_millis(100), false, None)
+            .unwrap();
+        let mut decoder = gif::DecodeOptions::new();
+        decoder.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = decoder.read_info(output.as_slice()).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (20, 10));
+        let frame = decoder.read_next_frame().unwrap().unwrap();
+        assert_eq!((frame.width, frame.height), (20, 10));
+        assert_eq!(frame.buffer[(9 * numx) * 4..][..3], [255; 3]);
+    }
+
+    #[test]
+    fn on_generation() {
+        use std::sync::mpsc;
+
+        let (sender, receiver) = mpsc::channel();
+        let gol = GameOfLifeStd::new(Array2::zeros((4, 3)), Rule::default());
+        let mut video = Video::new(gol).with_on_generation(move |generation, gol| {
+            sender.send((generation, gol.numx())).unwrap();
+        });
+        video.write_frames(io::sink(), 2, false, None).unwrap();
+        assert_eq!(
+            receiver.try_iter().collect::<Vec<_>>(),
+            [(1, 4), (2, 4), (3, 4)]
+        );
+    }
+
+    #[test]
+    fn gif_too_large() {
+        let gol = GameOfLifeStd::new(Array2::zeros((40000, 1)), Rule::default());
+        let mut gif = GIF::new(gol);
+        assert_eq!(gif.frame_size(), Ok((40000, 1)));
+
+        gif = gif.with_scale(2);
+        assert_eq!(
+            gif.frame_size(),
+            Err(GolError::FrameTooLarge {
+                width: 80000,
+                height: 2
+            })
+        );
+        let mut output = Vec::new();
+        let result = gif.start(&mut output, 0, Duration::from_millis(100), false, None);
+        assert_eq!(
+            result,
+            Err(GolError::FrameTooLarge {
+                width: 80000,
+                height: 2
+            })
+        );
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn gif_panic() {
+        let blinker = Array2::from_shape_fn((5, 5), |(x, y)| (y == 2 && (1..4).contains(&x)) as u8);
+        let mut gif = GIF::new(GameOfLifeStd::new(blinker, Rule::default())).with_on_generation(
+            |generation, _| {
+                if generation == 3 {
+                    panic!("injected failure");
+                }
+            },
+        );
+        let mut output = Vec::new();
+        let result = gif.start(&mut output, 10, Duration::from_millis(100), false, None);
+        assert_eq!(
+            result,
+            Err(GolError::Panicked("injected failure".to_owned()))
+        );
+
+        // The GIF ends after the two frames before the failing generation.
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(output.as_slice())
+            .unwrap();
+        let mut decoded = 0;
+        while decoder.read_next_frame().unwrap().is_some() {
+            decoded += 1;
+        }
+        assert_eq!(decoded, 2);
+        assert_eq!(output.last(), Some(&0x3b));
+    }
+
+    /// Writer that fails with `ErrorKind::StorageFull` once it received `capacity` bytes.
+    struct FullWriter {
+        written: Vec<u8>,
+        capacity: usize,
+    }
+
+    impl Write for FullWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let len = buf.len().min(self.capacity - self.written.len());
+            if len == 0 {
+                return Err(io::ErrorKind::StorageFull.into());
+            }
+            self.written.extend_from_slice(&buf[..len]);
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn gif_encoding_error() {
+        let blinker = Array2::from_shape_fn((5, 5), |(x, y)| (y == 2 && (1..4).contains(&x)) as u8);
+        let mut gif = GIF::new(GameOfLifeStd::new(blinker, Rule::default()));
+        let mut output = FullWriter {
+            written: Vec::new(),
+            capacity: 100,
+        };
+        let result = gif.start(&mut output, 10, Duration::from_millis(100), false, None);
+        let Err(e) = result else {
+            panic!("writing to a full writer succeeded");
+        };
+        assert!(matches!(e, GolError::Encoding(_)), "{e:?}");
+
+        // The kind of the failed write is kept as the source.
+        let source = std::error::Error::source(&e).unwrap();
+        let Some(EncodingError::Io(io)) = source.downcast_ref::<EncodingError>() else {
+            panic!("the source is not an I/O error: {source:?}");
+        };
+        assert_eq!(io.kind(), io::ErrorKind::StorageFull);
+        assert_eq!(output.written.len(), 100);
+    }
+
+    #[test]
+    fn gif_hold_last() {
+        let delays = |gif: &mut GIF<GameOfLifeStd>, iterations| {
+            let mut output = Vec::new();
+            gif.start(
+                &mut output,
+                iterations,
+                Duration::from_millis(100),
+                true,
+                None,
+            )
+            .unwrap();
+            let mut decoder = gif::DecodeOptions::new()
+                .read_info(output.as_slice())
+                .unwrap();
+            let mut delays = Vec::new();
+            while let Some(frame) = decoder.read_next_frame().unwrap() {
+                delays.push(frame.delay);
+            }
+            delays
+        };
+
+        let blinker = Array2::from_shape_fn((5, 5), |(x, y)| (y == 2 && (1..4).contains(&x)) as u8);
+        let mut gif = GIF::new(GameOfLifeStd::new(blinker, Rule::default()))
+            .with_hold_last(Duration::from_secs(2));
+        assert_eq!(delays(&mut gif, 3), [10, 10, 10, 200]);
+
+        // A single cell dies and the field stays empty, which ends the GIF early.
+        let mut field = Array2::zeros((5, 5));
+        field[[2, 2]] = 1;
+        let mut gif = GIF::new(GameOfLifeStd::new(field, Rule::default()))
+            .with_hold_last(Duration::from_secs(2));
+        assert_eq!(delays(&mut gif, 10), [10, 200]);
+    }
+
+    #[test]
+    fn gif_max_size() {
+        let gol = GameOfLifeStd::new(
+            Array2::from_shape_fn((40, 40), |(x, y)| ((x * 7 + y * 3) % 5 == 0) as u8),
+            Rule::default(),
+        );
+        let mut output = Vec::new();
+        let frames = GIF::new(gol)
+            .with_max_size(1000)
+            .start(&mut output, 50, Duration::from_millis(100), false, None)
+            .unwrap();
+        assert!(frames < 51);
+
+        // The truncated GIF is still complete.
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(output.as_slice())
+            .unwrap();
+        let mut decoded = 0;
+        while decoder.read_next_frame().unwrap().is_some() {
+            decoded += 1;
+        }
+        assert_eq!(decoded, frames);
+    }
+
+    #[test]
+    fn scaled_frame() {
+        let mut field = Array2::zeros((2, 1));
+        field[[1, 0]] = WALL;
+        let gol = GameOfLifeConvolution::new(field, Rule::default());
+
+        let pixels = render_frame(&gol, None, 3, 3);
+        assert_eq!(pixels.len(), 6 * 3 * 3);
+        let pixel = |x: usize, y: usize| &pixels[(y * 6 + x) * 3..][..3];
+        for y in 0..3 {
+            assert_eq!(pixel(2, y), [0; 3]);
+            assert_eq!(pixel(3, y), WALL_COLOR);
+        }
+
+        // Cells of 2x4 pixels, as for an aspect of 1:2 with a scale of 2.
+        let (cell_width, cell_height) = Aspect::from_str("1:2").unwrap().cell_size(2);
+        let pixels = render_frame(&gol, None, cell_width, cell_height);
+        assert_eq!(pixels.len(), 4 * 4 * 3);
+        let pixel = |x: usize, y: usize| &pixels[(y * 4 + x) * 3..][..3];
+        for y in 0..4 {
+            assert_eq!(pixel(1, y), [0; 3]);
+            assert_eq!(pixel(2, y), WALL_COLOR);
+        }
+    }
+
+    #[test]
+    fn golden_ppm() {
+        let blinker = Array2::from_shape_fn((3, 3), |(_, y)| (y == 1) as u8);
+        let gol = GameOfLifeStd::new(blinker, Rule::default());
+        assert_eq!(
+            gol.to_board().to_ppm(2),
+            include_bytes!("../tests/golden/blinker.ppm")
+        );
+    }
+
+    #[test]
+    fn trail() {
+        // A blinker, whose ends die and are reborn every other generation.
+        let blinker = Array2::from_shape_fn((3, 3), |(_, y)| (y == 1) as u8);
+        let mut gol = GameOfLifeStd::new(blinker, Rule::default());
+        let mut trail = Trail::new(&gol, 4).unwrap();
+
+        trail.update(&gol);
+        gol.compute_next_generation();
+        let pixels = render_frame(&gol, Some(&trail), 1, 1);
+        let pixel = |x: usize, y: usize| &pixels[(y * 3 + x) * 3..][..3];
+        assert_eq!(pixel(0, 1), TRAIL_COLOR);
+        assert_eq!(pixel(1, 0), [255; 3]);
+        assert_eq!(pixel(0, 0), [0; 3]);
+
+        // The trail fades even while the cell stays dead.
+        trail.update(&gol);
+        assert_eq!(
+            trail.color(0, 1),
+            Some(TRAIL_COLOR.map(|c| (c as u32 * 3 / 4) as u8))
+        );
+        for _ in 0..3 {
+            trail.update(&gol);
+        }
+        assert_eq!(trail.color(0, 1), None);
+        assert!(Trail::new(&gol, 0).is_none());
+    }
+
+    #[test]
+    fn size_in_tiny_terminals() {
+        assert_eq!(size_in_terminal(None, None, Some((80, 24))), (78, 22));
+        assert_eq!(size_in_terminal(None, None, Some((1, 1))), (1, 1));
+        assert_eq!(size_in_terminal(None, None, Some((0, 3))), (1, 1));
+        assert_eq!(size_in_terminal(None, None, None), (10, 10));
+        assert_eq!(size_in_terminal(Some(5), None, Some((2, 2))), (5, 1));
+        assert_eq!(size_in_terminal(None, Some(7), None), (10, 7));
+    }
+
+    #[test]
+    fn shades() {
+        assert_eq!(shade(0, 4, false), None);
+        assert_eq!(shade(0, 4, true), Some(TRAIL_SHADE));
+        assert_eq!(shade(WALL, 4, false), Some(WALL_SHADE));
+        assert_eq!(shade(4, 4, false), Some(255));
+        assert_eq!(shade(1, 4, false), Some(242));
+        assert_eq!(shade(1, 1, true), Some(255));
+
+        // Fresher cells are brighter.
+        let grays: Vec<u8> = (1..=16)
+            .map(|cell| shade(cell, 16, false).unwrap())
+            .collect();
+        assert!(grays.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(grays.iter().all(|gray| (238..=255).contains(gray)));
+    }
+
+    #[test]
+    fn viewport() {
+        assert_eq!(viewport_size(200, 100, Some((82, 24))), (80, 22));
+        assert_eq!(viewport_size(20, 10, Some((82, 24))), (20, 10));
+        assert_eq!(viewport_size(200, 100, None), (200, 100));
+
+        assert_eq!(pan(0, 1, 80, 200), 20);
+        assert_eq!(pan(100, 1, 80, 200), 120);
+        assert_eq!(pan(120, 1, 80, 200), 120);
+        assert_eq!(pan(10, -1, 80, 200), 0);
+        assert_eq!(pan(0, 1, 2, 5), 1);
+        // Fields that fit into the view cannot be moved.
+        assert_eq!(pan(0, 1, 20, 20), 0);
+    }
+
+    #[test]
+    fn polling() {
+        let now = Instant::now();
+        let ms = Duration::from_millis;
+        assert_eq!(
+            poll_timeout(now + ms(500), now, false),
+            Some(POLLING_INTERVAL)
+        );
+        assert_eq!(poll_timeout(now + ms(5), now, false), Some(ms(5)));
+        assert_eq!(poll_timeout(now, now, false), None);
+        assert_eq!(poll_timeout(now, now + ms(100), false), None);
+        assert_eq!(
+            poll_timeout(now, now + ms(100), true),
+            Some(POLLING_INTERVAL)
+        );
+    }
+
+    #[test]
+    fn resolution() {
+        let resolution = Resolution::from_str("1920x1080").unwrap();
+        assert_eq!(resolution.to_string(), "1920x1080");
+        assert_eq!(resolution.field_size(4, 4), (480, 270));
+        assert_eq!(resolution.field_size(4, 8), (480, 135));
+        assert_eq!(resolution.field_size(5000, 5000), (1, 1));
+        for invalid in ["", "1920", "0x10", "x10", "1920x-1", "axb"] {
+            assert_eq!(Resolution::from_str(invalid), Err(()), "{invalid}");
+        }
+
+        assert_eq!(Aspect::from_str("1:2").unwrap().to_string(), "1:2");
+        for invalid in ["", "1", "0:1", "1x2", "-1:2"] {
+            assert_eq!(Aspect::from_str(invalid), Err(()), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn snapshot() {
+        let glider = Pattern::Glider.place(8, 6, 1).unwrap();
+        let mut gol = GameOfLifeStd::new(glider.clone(), Rule::default());
+        let mut output = Vec::new();
+        Snapshot::new(gol.clone())
+            .with_scale(2)
+            .write(&mut output, 4, false, None)
+            .unwrap();
+
+        let mut reader = png::Decoder::new(output.as_slice()).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (16, 12));
+        for _ in 0..4 {
+            gol.compute_next_generation();
+        }
+        assert_eq!(pixels, render_frame(&gol, None, 2, 2));
+
+        // Generation 0 is the initial field.
+        let mut output = Vec::new();
+        Snapshot::new(GameOfLifeStd::new(glider.clone(), Rule::default()))
+            .write(&mut output, 0, false, None)
+            .unwrap();
+        let mut reader = png::Decoder::new(output.as_slice()).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut pixels).unwrap();
+        let gol = GameOfLifeStd::new(glider, Rule::default());
+        assert_eq!(pixels, render_frame(&gol, None, 1, 1));
+    }
+
+    #[test]
+    fn video_frames() {
+        let blinker = Array2::from_shape_fn((5, 4), |(x, y)| (y == 1 && x < 3) as u8);
+        let mut output = Vec::new();
+        Video::new(GameOfLifeStd::new(blinker, Rule::default()))
+            .write_frames(&mut output, 3, true, None)
+            .unwrap();
+        assert_eq!(output.len(), 4 * 5 * 4 * 3);
+
+        // An empty field is stable right away, so only the first frame is written.
+        let mut output = Vec::new();
+        Video::new(GameOfLifeStd::new(Array2::zeros((5, 4)), Rule::default()))
+            .write_frames(&mut output, 3, true, None)
+            .unwrap();
+        assert_eq!(output.len(), 5 * 4 * 3);
+    }
+}
@@ -0,0 +1,209 @@
+//! # Summary
+//! Contains the report printed with `--summary` at the end of a headless run.
+
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use std::str::FromStr;
+use std::time::Duration;
+
+use game_of_life::gameoflife::*;
+use game_of_life::presentation::OnGeneration;
+use ndarray::Array2;
+
+/// Output formats of the summary
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SummaryFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for SummaryFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(SummaryFormat::Text),
+            "json" => Ok(SummaryFormat::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for SummaryFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            SummaryFormat::Text => write!(f, "text"),
+            SummaryFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Number of recent fields kept to detect repetitions, which is the longest period that is found
+const MAX_PERIOD: usize = 16;
+
+/// How a run ended, collected by [`Summary::hook`] while the presentation computes the generations
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Summary<T: Cell = u8> {
+    /// Number of computed generations, which includes the one after the last frame
+    pub generations: usize,
+    /// Number of living cells in the last generation
+    pub population: usize,
+    /// First generation that the next one did not change
+    pub stable_after: Option<usize>,
+    /// Number of generations after which the field repeats, only for oscillators with a period from 2 to [`MAX_PERIOD`]
+    pub period: Option<usize>,
+    /// Wall-clock time of the whole run
+    pub elapsed: Duration,
+    /// Generation, hash, and field of the last [`MAX_PERIOD`] generations
+    recent: VecDeque<(usize, u64, Array2<T>)>,
+}
+
+impl<T: Cell> Summary<T> {
+    /// Records the initial field of `gameoflife` and returns a hook for the presentations that records every generation computed after it.
+    pub fn hook<G: GameOfLife<Data = T>>(
+        summary: &Rc<RefCell<Self>>,
+        gameoflife: &G,
+    ) -> OnGeneration<G> {
+        summary
+            .borrow_mut()
+            .record(0, &gameoflife.snapshot(), gameoflife.state());
+        let summary = Rc::clone(summary);
+        Box::new(move |generation, gameoflife| {
+            summary
+                .borrow_mut()
+                .record(generation, &gameoflife.snapshot(), gameoflife.state())
+        })
+    }
+
+    /// Records the field of `generation` and detects whether the field stopped changing or repeats.
+    fn record(&mut self, generation: usize, field: &Array2<T>, state: T) {
+        let changed = self
+            .recent
+            .back()
+            .is_none_or(|(_, _, previous)| previous != field);
+        self.generations = generation;
+        self.population = field.iter().filter(|&&cell| cell == state).count();
+        if !changed && self.stable_after.is_none() {
+            self.stable_after = Some(generation - 1);
+        }
+
+        let mut hasher = DefaultHasher::new();
+        field.hash(&mut hasher);
+        let hash = hasher.finish();
+        // Hashes can collide, so only an equal field counts as a repetition.
+        let previous = self
+            .recent
+            .iter()
+            .rev()
+            .find(|(_, other_hash, other)| *other_hash == hash && other == field);
+        if let Some(&(first, ..)) = previous {
+            if self.period.is_none() && generation - first > 1 {
+                self.period = Some(generation - first);
+            }
+        }
+        if self.recent.len() == MAX_PERIOD {
+            self.recent.pop_front();
+        }
+        self.recent.push_back((generation, hash, field.clone()));
+    }
+
+    /// Formats the summary as lines of text or as a JSON object.
+    pub fn format(&self, format: SummaryFormat) -> String {
+        let optional = |value: Option<usize>, none: &str| {
+            value.map_or(none.to_owned(), |value| value.to_string())
+        };
+        match format {
+            SummaryFormat::Text => format!(
+                "Generations computed: {}\nPopulation: {}\nStable after: {}\nPeriod: {}\nTime: {:.2?}",
+                self.generations,
+                self.population,
+                optional(self.stable_after, "never"),
+                optional(self.period, "none"),
+                self.elapsed,
+            ),
+            SummaryFormat::Json => format!(
+                r#"{{"generations":{},"population":{},"stable_after":{},"period":{},"elapsed_ms":{}}}"#,
+                self.generations,
+                self.population,
+                optional(self.stable_after, "null"),
+                optional(self.period, "null"),
+                self.elapsed.as_millis(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use game_of_life::patterns::Pattern;
+
+    use super::*;
+
+    fn run(pattern: Pattern, size: usize, generations: usize) -> Summary {
+        let field = pattern.place(size, size, 1).unwrap();
+        let mut gol = GameOfLifeStd::new(field, Rule::default());
+        let summary = Rc::default();
+        let mut hook = Summary::hook(&summary, &gol);
+        for generation in 1..=generations {
+            gol.compute_next_generation();
+            hook(generation, &gol);
+        }
+        let summary = summary.borrow().clone();
+        summary
+    }
+
+    #[test]
+    fn oscillator() {
+        let summary = run(Pattern::Blinker, 12, 5);
+        assert_eq!(summary.generations, 5);
+        assert_eq!(summary.population, 3);
+        assert_eq!(summary.stable_after, None);
+        assert_eq!(summary.period, Some(2));
+    }
+
+    #[test]
+    fn long_period() {
+        // The pulsar has a period of 3, and the window forgets fields older than `MAX_PERIOD` generations.
+        let summary = run(Pattern::Pulsar, 17, 100);
+        assert_eq!(summary.period, Some(3));
+        assert_eq!(summary.recent.len(), MAX_PERIOD);
+    }
+
+    #[test]
+    fn hash_collision() {
+        // A different field with the same hash does not count as a repetition.
+        let field = Pattern::Glider.place(8, 8, 1).unwrap();
+        let mut hasher = DefaultHasher::new();
+        field.hash(&mut hasher);
+        let mut summary = Summary {
+            recent: VecDeque::from([(0, hasher.finish(), Array2::zeros((8, 8)))]),
+            ..Summary::default()
+        };
+        summary.record(5, &field, 1);
+        assert_eq!(summary.period, None);
+        summary.record(7, &field, 1);
+        assert_eq!(summary.period, Some(2));
+    }
+
+    #[test]
+    fn still_life() {
+        // A glider ends up as a block in the corner of a bounded field.
+        let summary = run(Pattern::Glider, 12, 60);
+        assert_eq!(summary.population, 4);
+        assert!(summary
+            .stable_after
+            .is_some_and(|generation| generation < 60));
+        assert_eq!(summary.period, None);
+        assert_eq!(
+            summary.format(SummaryFormat::Json),
+            format!(
+                r#"{{"generations":60,"population":4,"stable_after":{},"period":null,"elapsed_ms":0}}"#,
+                summary.stable_after.unwrap()
+            )
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! # WASM
+//! Contains a minimal interface to run a Game of Life in the browser with [`wasm-bindgen`](https://rustwasm.github.io/docs/wasm-bindgen/).
+//!
+//! ```js
+//! const universe = new Universe(80, 60, "B3/S23", 0.3, 42);
+//! universe.step();
+//! const cells = new Uint8Array(memory.buffer, universe.cells_ptr(), universe.width() * universe.height());
+//! ```
+
+use std::str::FromStr;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use wasm_bindgen::prelude::*;
+
+use crate::gameoflife::*;
+
+/// Game of Life using the convolution, which does not need threads.
+///
+/// After every generation, the cells are copied row by row into a buffer, which JavaScript can read from the memory of the module at [`Universe::cells_ptr`] to draw them on a canvas.
+#[wasm_bindgen]
+pub struct Universe {
+    gol: GameOfLifeConvolution,
+    cells: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl Universe {
+    /// Fills a field of `width` x `height` cells randomly, with each cell being alive with `probability`.
+    /// `rule` is a rulestring like `B3/S23`, see [`Rule::from_str`].
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        width: usize,
+        height: usize,
+        rule: &str,
+        probability: f64,
+        seed: u32,
+    ) -> Result<Universe, JsError> {
+        let rule = Rule::from_str(rule).map_err(|_| JsError::new("invalid rule"))?;
+        check_state::<u8>(rule.state).map_err(|e| JsError::new(&e.to_string()))?;
+        check_dimensions(width, height, MAX_CELLS).map_err(|e| JsError::new(&e.to_string()))?;
+        if !(0.0..=1.0).contains(&probability) {
+            return Err(JsError::new("the probability has to be between 0 and 1"));
+        }
+
+        let mut rng = StdRng::seed_from_u64(seed as u64);
+        let mut universe = Universe {
+            gol: GameOfLifeConvolution::random(width, height, rule, probability, &mut rng),
+            cells: Vec::with_capacity(width * height),
+        };
+        universe.copy_cells();
+        Ok(universe)
+    }
+
+    /// Computes the next generation and returns whether any cell changed.
+    pub fn step(&mut self) -> bool {
+        let changed = self.gol.compute_next_generation();
+        self.copy_cells();
+        changed
+    }
+
+    /// Returns a pointer to the `width * height` cells, row by row.
+    /// Living cells have the value of [`Universe::state`], dying cells count down to 0, and walls are 255.
+    pub fn cells_ptr(&self) -> *const u8 {
+        self.cells.as_ptr()
+    }
+
+    pub fn width(&self) -> usize {
+        self.gol.numx()
+    }
+
+    pub fn height(&self) -> usize {
+        self.gol.numy()
+    }
+
+    pub fn state(&self) -> u8 {
+        self.gol.state()
+    }
+}
+
+impl Universe {
+    /// Copies the field into the buffer, transposed as the field is indexed by `[x, y]`.
+    fn copy_cells(&mut self) {
+        self.cells.clear();
+        self.cells.extend(self.gol.snapshot().t().iter());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cells_row_by_row() {
+        let mut universe = Universe::new(5, 3, "B3/S23", 0., 0).unwrap();
+        universe.gol.set_cell(1, 1, 1);
+        universe.gol.set_cell(2, 1, 1);
+        universe.gol.set_cell(3, 1, 1);
+        assert!(universe.step());
+
+        // The blinker turned vertical.
+        assert_eq!((universe.width(), universe.height()), (5, 3));
+        assert_eq!(
+            universe.cells,
+            [0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0]
+        );
+        assert_eq!(universe.cells_ptr(), universe.cells.as_ptr());
+    }
+}
@@ -0,0 +1,75 @@
+//! Runs the binary to check how it handles the command line.
+
+use std::process::Command;
+
+/// Runs the binary with `args` and returns its exit code and what it printed to stderr.
+fn run(args: &[&str]) -> (Option<i32>, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_game-of-life"))
+        .args(args)
+        .output()
+        .expect("running the binary");
+    (
+        output.status.code(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    )
+}
+
+#[test]
+fn unreachable_neighbor_count() {
+    // A von Neumann neighborhood has at most 4 living neighbors.
+    let (code, stderr) = run(&["-n", "vn", "-r", "B5/S23", "-i", "1", "bench"]);
+    assert_eq!(code, Some(78), "{stderr}");
+    assert!(stderr.contains("at most 4 living neighbors"), "{stderr}");
+
+    let (code, stderr) = run(&["-n", "vn", "-r", "B3/S23", "-i", "1", "bench"]);
+    assert_eq!(code, Some(0), "{stderr}");
+}
+
+#[test]
+fn unreachable_kernel_count() {
+    // The weights of the kernel sum up to 4.
+    let kernel = std::env::temp_dir().join(format!("kernel-{}.csv", std::process::id()));
+    std::fs::write(&kernel, "0,1,0\n1,0,1\n0,1,0\n").unwrap();
+    let kernel = kernel.to_str().unwrap();
+
+    let (code, stderr) = run(&["--kernel", kernel, "-r", "B5/S23", "-i", "1", "bench"]);
+    assert_eq!(code, Some(78), "{stderr}");
+    assert!(stderr.contains("at most 4 living neighbors"), "{stderr}");
+
+    let (code, stderr) = run(&["--kernel", kernel, "-r", "B3/S23", "-i", "1", "bench"]);
+    assert_eq!(code, Some(0), "{stderr}");
+    std::fs::remove_file(kernel).unwrap();
+}
+
+#[test]
+fn dump_initial_multi_state() {
+    let dump = std::env::temp_dir().join(format!("initial-{}.cells", std::process::id()));
+    let dump = dump.to_str().unwrap();
+    let args = |init_states| {
+        [
+            "-r",
+            "B3/S23/C4",
+            "--init-states",
+            init_states,
+            "--seed",
+            "1",
+            "--dump-initial",
+            dump,
+            "-i",
+            "1",
+            "bench",
+        ]
+    };
+
+    // Decaying cells would be saved as dead ones.
+    let (code, stderr) = run(&args("random"));
+    assert_eq!(code, Some(78), "{stderr}");
+    assert!(
+        stderr.contains("only keeps living and dead cells"),
+        "{stderr}"
+    );
+
+    let (code, stderr) = run(&args("max"));
+    assert_eq!(code, Some(0), "{stderr}");
+    std::fs::remove_file(dump).unwrap();
+}